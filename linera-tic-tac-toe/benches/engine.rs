@@ -0,0 +1,151 @@
+//! Engine benchmarks: the bitboard core against the array-of-`Option` scans it replaced.
+//!
+//! Run with `cargo bench --bench engine`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use linera_tic_tac_toe::bitboard::{BitPosition, LineMasks};
+use linera_tic_tac_toe::solver::Solver;
+use linera_tic_tac_toe::{BoardConfig, Player};
+use std::collections::HashMap;
+
+/// Boards the engine supports, from classic play to Go-sized Gomoku
+fn boards() -> Vec<(&'static str, BoardConfig)> {
+    vec![
+        ("3x3", BoardConfig::default()),
+        ("15x15", BoardConfig::new(15, 15, 5).unwrap()),
+        ("19x19", BoardConfig::new(19, 19, 5).unwrap()),
+        (
+            "4x4x4",
+            BoardConfig {
+                width: 4,
+                height: 4,
+                depth: 4,
+                win_length: 4,
+            },
+        ),
+    ]
+}
+
+/// A half-filled position, the worst case for a win check that finds nothing early
+fn mid_game(config: &BoardConfig) -> Vec<Option<Player>> {
+    (0..config.cell_count())
+        .map(|cell| match (cell * 7 + cell / config.width) % 4 {
+            0 => Some(Player::X),
+            1 => Some(Player::O),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Win check as done before bitboards: walk every line cell by cell
+fn array_find_line(board: &[Option<Player>], lines: &[Vec<usize>]) -> Option<Player> {
+    lines.iter().find_map(|line| match board[line[0]] {
+        Some(first) if line.iter().all(|&cell| board[cell] == Some(first)) => Some(first),
+        _ => None,
+    })
+}
+
+/// Memoized negamax over arrays, as the solver searched before bitboards
+fn array_negamax(
+    board: &mut [Option<Player>; 9],
+    to_move: Player,
+    lines: &[Vec<usize>],
+    cache: &mut HashMap<([Option<Player>; 9], Player), i8>,
+) -> i8 {
+    if array_find_line(board, lines).is_some() {
+        return -1;
+    }
+    if board.iter().all(|cell| cell.is_some()) {
+        return 0;
+    }
+    if let Some(&value) = cache.get(&(*board, to_move)) {
+        return value;
+    }
+
+    let mut best = -1;
+    for cell in 0..9 {
+        if board[cell].is_none() {
+            board[cell] = Some(to_move);
+            best = best.max(-array_negamax(board, to_move.opponent(), lines, cache));
+            board[cell] = None;
+        }
+    }
+    cache.insert((*board, to_move), best);
+    best
+}
+
+fn win_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("win_check");
+    for (name, config) in boards() {
+        let board = mid_game(&config);
+        let lines = config.winning_lines();
+        let masks = LineMasks::new(&config);
+        let position = BitPosition::from_cells(&board);
+        assert_eq!(
+            array_find_line(&board, &lines),
+            position.winning_line(&masks).map(|(symbol, _)| symbol)
+        );
+
+        group.bench_with_input(BenchmarkId::new("array", name), &board, |b, board| {
+            b.iter(|| array_find_line(black_box(board), &lines))
+        });
+        group.bench_with_input(BenchmarkId::new("bitboard", name), &position, |b, position| {
+            b.iter(|| black_box(position).winning_line(&masks).is_some())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("bitboard_from_cells", name),
+            &board,
+            |b, board| {
+                b.iter(|| {
+                    BitPosition::from_cells(black_box(board))
+                        .winning_line(&masks)
+                        .is_some()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn move_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move_generation");
+    for (name, config) in boards() {
+        let board = mid_game(&config);
+        let masks = LineMasks::new(&config);
+        let position = BitPosition::from_cells(&board);
+
+        group.bench_with_input(BenchmarkId::new("array", name), &board, |b, board| {
+            b.iter(|| {
+                black_box(board)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_none())
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bitboard", name), &position, |b, position| {
+            b.iter(|| black_box(position).empty_cells(&masks).cells().collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+}
+
+fn solver(c: &mut Criterion) {
+    let lines = BoardConfig::default().winning_lines();
+    let mut group = c.benchmark_group("solve_empty_3x3");
+
+    group.bench_function("array", |b| {
+        b.iter(|| {
+            let mut cache = HashMap::new();
+            array_negamax(&mut [None; 9], Player::X, &lines, &mut cache)
+        })
+    });
+    group.bench_function("bitboard", |b| {
+        b.iter(|| Solver::new().solve_board(black_box(&[None; 9]), Player::X))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, win_check, move_generation, solver);
+criterion_main!(benches);
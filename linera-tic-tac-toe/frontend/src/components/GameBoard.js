@@ -111,21 +111,10 @@ const GameBoard = ({ game, playerId, onMove, onBackToGames }) => {
       className += ' selected';
     }
     
-    // Add winning line highlight if game is finished
-    if (game.status === 'finished' && game.winner) {
-      const winningLines = [
-        [0, 1, 2], [3, 4, 5], [6, 7, 8], // rows
-        [0, 3, 6], [1, 4, 7], [2, 5, 8], // columns
-        [0, 4, 8], [2, 4, 6] // diagonals
-      ];
-      
-      const winningLine = winningLines.find(line => 
-        line.every(pos => game.board[pos] === game.winner)
-      );
-      
-      if (winningLine && winningLine.includes(index)) {
-        className += ' winning-cell';
-      }
+    // Highlight the winning line reported by the contract
+    const winningLine = game.outcome?.winning_line || [];
+    if (winningLine.includes(index)) {
+      className += ' winning-cell';
     }
     
     return className;
@@ -177,6 +177,7 @@ describe('GameBoard Component', () => {
       status: 'finished',
       winner: 'X',
       board: ['X', 'X', 'X', 'O', 'O', null, null, null, null],
+      outcome: { reason: 'LineCompleted', winning_line: [0, 1, 2], move_number: 5 },
       finished_at: '2024-01-01T01:00:00Z'
     };
     
@@ -0,0 +1,256 @@
+//! Post-game analysis: replay a finished game and judge every move.
+//!
+//! Classic 3x3 games are judged against the solver, so each move carries the
+//! exact value before and after it. Larger boards fall back to the hint
+//! heuristic, which only catches one-move wins and one-move losses.
+
+use crate::hint::{self, HintReason};
+use crate::solver::{Solver, Value};
+use crate::{Game, GameId, GameStatus, Move, PlayerId, TicTacToeError};
+use serde::{Deserialize, Serialize};
+
+/// Something worth pointing out about a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveFlag {
+    /// The move made the mover's position worse
+    Blunder,
+    /// A forced win was available and the move let it go
+    MissedWin,
+}
+
+/// Judgement of a single move
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveAnalysis {
+    /// 1-based number of the move in the game
+    pub move_number: usize,
+    pub player_id: PlayerId,
+    pub mv: Move,
+    /// Value for the mover before the move, when the position could be solved
+    pub value_before: Option<Value>,
+    /// Value for the mover after the move, when the position could be solved
+    pub value_after: Option<Value>,
+    /// Moves that would have kept the best value, empty when not solved
+    pub best_moves: Vec<Move>,
+    pub flags: Vec<MoveFlag>,
+}
+
+/// Move-by-move report on a finished game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameAnalysis {
+    pub game_id: GameId,
+    /// Whether values come from an exact solve rather than the heuristic
+    pub exact: bool,
+    pub moves: Vec<MoveAnalysis>,
+}
+
+impl GameAnalysis {
+    /// Number of flagged moves made by `player_id`
+    pub fn count(&self, player_id: &PlayerId, flag: MoveFlag) -> usize {
+        self.moves
+            .iter()
+            .filter(|analysis| analysis.player_id == *player_id && analysis.flags.contains(&flag))
+            .count()
+    }
+}
+
+/// Replay a finished game and analyse every move
+pub fn analyse(game: &Game) -> Result<GameAnalysis, TicTacToeError> {
+    if game.status != GameStatus::Finished {
+        return Err(TicTacToeError::GameNotFinished);
+    }
+
+    let mut position = starting_position(game)?;
+    let mut solver = if game.options.misere {
+        Solver::misere()
+    } else {
+        Solver::new()
+    };
+    let exact = solver.solve(&position).is_ok();
+
+    let mut moves = Vec::with_capacity(game.moves.len());
+    for (index, record) in game.moves.iter().enumerate() {
+        let analysis = if exact {
+            let before = solver.solve(&position)?;
+            position.play(&record.player_id, record.mv, record.timestamp)?;
+            let value_after = mover_value(&mut solver, &position, &record.player_id)?;
+
+            let mut flags = Vec::new();
+            if value_after < before.value {
+                flags.push(MoveFlag::Blunder);
+            }
+            if before.value == Value::Win && value_after != Value::Win {
+                flags.push(MoveFlag::MissedWin);
+            }
+            MoveAnalysis {
+                move_number: index + 1,
+                player_id: record.player_id.clone(),
+                mv: record.mv,
+                value_before: Some(before.value),
+                value_after: Some(value_after),
+                best_moves: before.best_moves.into_iter().map(Move::at).collect(),
+                flags,
+            }
+        } else {
+            let hints = hint::hints(&position, &record.player_id)?;
+            position.play(&record.player_id, record.mv, record.timestamp)?;
+
+            let best = hints.first().map(|hint| hint.reason);
+            let played = hints
+                .iter()
+                .find(|hint| {
+                    hint.mv.position == record.mv.position
+                        && hint.mv.sub_board == record.mv.sub_board
+                })
+                .map(|hint| hint.reason);
+
+            let mut flags = Vec::new();
+            if played == Some(HintReason::GivesOpponentWin)
+                && best != Some(HintReason::GivesOpponentWin)
+            {
+                flags.push(MoveFlag::Blunder);
+            }
+            if best == Some(HintReason::WinsGame) && played != Some(HintReason::WinsGame) {
+                flags.push(MoveFlag::MissedWin);
+            }
+            MoveAnalysis {
+                move_number: index + 1,
+                player_id: record.player_id.clone(),
+                mv: record.mv,
+                value_before: None,
+                value_after: None,
+                best_moves: Vec::new(),
+                flags,
+            }
+        };
+        moves.push(analysis);
+    }
+
+    Ok(GameAnalysis {
+        game_id: game.id,
+        exact,
+        moves,
+    })
+}
+
+/// The game as it stood before the first move
+fn starting_position(game: &Game) -> Result<Game, TicTacToeError> {
+    let mut seats = game.players.iter();
+    let first = seats.next().ok_or(TicTacToeError::PlayerNotInGame)?;
+    let mut position = Game::with_variant(
+        game.id,
+        first.id.clone(),
+        first.name.clone(),
+        game.variant,
+        game.config,
+        game.created_at,
+    )
+    .with_options(game.options);
+    for player in seats {
+        position.add_player(player.id.clone(), player.name.clone())?;
+    }
+    Ok(position)
+}
+
+/// Value of the position for the player who just moved
+fn mover_value(
+    solver: &mut Solver,
+    position: &Game,
+    mover: &PlayerId,
+) -> Result<Value, TicTacToeError> {
+    if position.status == GameStatus::Finished {
+        return Ok(match &position.winner_id {
+            Some(winner) if winner == mover => Value::Win,
+            Some(_) => Value::Loss,
+            None => Value::Draw,
+        });
+    }
+    Ok(solver.solve(position)?.value.flip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardConfig, GameVariant};
+    use linera_base::data_types::Timestamp;
+
+    fn finished_game(variant: GameVariant, config: BoardConfig, moves: &[usize]) -> Game {
+        let mut game = Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            variant,
+            config,
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        for &position in moves {
+            let mover = game.player_to_move().unwrap().id.clone();
+            game.make_move(&mover, position, Timestamp::from(1)).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn test_edge_reply_to_corner_is_a_blunder() {
+        // X corner, O edge loses; X converts
+        let game = finished_game(
+            GameVariant::Standard,
+            BoardConfig::default(),
+            &[0, 1, 4, 8, 6, 3, 2],
+        );
+        let analysis = analyse(&game).unwrap();
+
+        assert!(analysis.exact);
+        assert_eq!(analysis.moves.len(), 7);
+
+        let reply = &analysis.moves[1];
+        assert_eq!(reply.player_id, "bob");
+        assert_eq!(reply.value_before, Some(Value::Draw));
+        assert_eq!(reply.value_after, Some(Value::Loss));
+        assert_eq!(reply.flags, vec![MoveFlag::Blunder]);
+        assert_eq!(reply.best_moves, vec![Move::at(4)]);
+
+        assert!(analysis.moves[0].flags.is_empty());
+        assert_eq!(analysis.moves[6].value_after, Some(Value::Win));
+        assert_eq!(analysis.count(&"alice".to_string(), MoveFlag::Blunder), 0);
+    }
+
+    #[test]
+    fn test_letting_a_forced_win_go_is_flagged() {
+        // X forks with 6, could win at 7 on move seven but blocks at 5; drawn
+        let game = finished_game(
+            GameVariant::Standard,
+            BoardConfig::default(),
+            &[0, 4, 8, 2, 6, 3, 5, 7, 1],
+        );
+        assert_eq!(game.winner_id, None);
+        let analysis = analyse(&game).unwrap();
+
+        let miss = &analysis.moves[6];
+        assert_eq!(miss.value_before, Some(Value::Win));
+        assert_eq!(miss.value_after, Some(Value::Draw));
+        assert!(miss.flags.contains(&MoveFlag::MissedWin));
+        assert!(miss.flags.contains(&MoveFlag::Blunder));
+        assert_eq!(analysis.count(&"alice".to_string(), MoveFlag::MissedWin), 1);
+    }
+
+    #[test]
+    fn test_large_boards_use_the_heuristic() {
+        // 4x4, 3 in a row: X ignores O's threat at 2 and loses
+        let config = BoardConfig::new(4, 4, 3).unwrap();
+        let game = finished_game(GameVariant::Standard, config, &[15, 0, 12, 1, 9, 2]);
+        assert_eq!(game.winner_id.as_deref(), Some("bob"));
+        let analysis = analyse(&game).unwrap();
+
+        assert!(!analysis.exact);
+        assert!(analysis.moves.iter().all(|m| m.value_before.is_none()));
+        assert!(analysis.moves[4].flags.contains(&MoveFlag::Blunder));
+    }
+
+    #[test]
+    fn test_unfinished_games_are_not_analysed() {
+        let game = finished_game(GameVariant::Standard, BoardConfig::default(), &[4]);
+
+        assert!(matches!(analyse(&game), Err(TicTacToeError::GameNotFinished)));
+    }
+}
@@ -0,0 +1,337 @@
+//! Bitboard core of the game engine.
+//!
+//! A position is one bitmask per side, and every winning line of a board is
+//! precomputed as a mask, so win checks and move generation are a handful of
+//! word operations instead of walks over `Vec<Option<Player>>`. `Game::board`
+//! keeps its serialized shape; bitboards are built from it where speed matters.
+
+use crate::{BoardConfig, Player};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::{BitAnd, BitOr};
+use std::rc::Rc;
+
+/// 64-bit words in a bitboard
+const WORDS: usize = 6;
+
+/// Cells a bitboard can hold, enough for the largest 19x19 board
+pub const MAX_CELLS: usize = WORDS * 64;
+
+/// A set of board cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bitboard([u64; WORDS]);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard([0; WORDS]);
+
+    /// The first `count` cells
+    pub fn first(count: usize) -> Self {
+        let mut board = Self::EMPTY;
+        for (word, bits) in board.0.iter_mut().enumerate() {
+            let start = word * 64;
+            *bits = match count.saturating_sub(start) {
+                0 => 0,
+                n if n >= 64 => u64::MAX,
+                n => (1 << n) - 1,
+            };
+        }
+        board
+    }
+
+    /// The set holding only `cell`
+    pub fn single(cell: usize) -> Self {
+        let mut board = Self::EMPTY;
+        board.set(cell);
+        board
+    }
+
+    pub fn set(&mut self, cell: usize) {
+        self.0[cell / 64] |= 1 << (cell % 64);
+    }
+
+    pub fn clear(&mut self, cell: usize) {
+        self.0[cell / 64] &= !(1 << (cell % 64));
+    }
+
+    pub fn contains(&self, cell: usize) -> bool {
+        self.0[cell / 64] & (1 << (cell % 64)) != 0
+    }
+
+    /// Whether every cell of `mask` is in the set
+    pub fn contains_all(&self, mask: &Bitboard) -> bool {
+        self.0.iter().zip(&mask.0).all(|(bits, mask)| bits & mask == *mask)
+    }
+
+    /// Whether the set shares a cell with `mask`
+    pub fn intersects(&self, mask: &Bitboard) -> bool {
+        self.0.iter().zip(&mask.0).any(|(bits, mask)| bits & mask != 0)
+    }
+
+    /// Cells in the set but not in `other`
+    pub fn without(&self, other: &Bitboard) -> Bitboard {
+        Bitboard(std::array::from_fn(|word| self.0[word] & !other.0[word]))
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|bits| bits.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&bits| bits == 0)
+    }
+
+    /// Cells in the set, in increasing order
+    pub fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word, &bits)| {
+            let mut bits = bits;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(word * 64 + bit)
+            })
+        })
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        Bitboard(std::array::from_fn(|word| self.0[word] & other.0[word]))
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        Bitboard(std::array::from_fn(|word| self.0[word] | other.0[word]))
+    }
+}
+
+impl FromIterator<usize> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = usize>>(cells: I) -> Self {
+        let mut board = Self::EMPTY;
+        for cell in cells {
+            board.set(cell);
+        }
+        board
+    }
+}
+
+/// Winning lines of one board, as masks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMasks {
+    /// Every cell of the board
+    pub board: Bitboard,
+    pub lines: Vec<Bitboard>,
+    /// First cell of each line, checked before the whole mask
+    first_cells: Vec<usize>,
+    /// Indices into `lines` of the lines through each cell
+    through: Vec<Vec<usize>>,
+}
+
+thread_local! {
+    static LINE_MASKS: RefCell<HashMap<BoardConfig, Rc<LineMasks>>> = RefCell::new(HashMap::new());
+}
+
+impl LineMasks {
+    pub fn new(config: &BoardConfig) -> Self {
+        let cell_count = config.cell_count();
+        assert!(cell_count <= MAX_CELLS, "board too large for a bitboard");
+
+        let winning_lines = config.winning_lines();
+        let first_cells = winning_lines.iter().map(|line| line[0]).collect();
+        let lines: Vec<Bitboard> = winning_lines
+            .into_iter()
+            .map(|line| line.into_iter().collect())
+            .collect();
+        let mut through = vec![Vec::new(); cell_count];
+        for (index, line) in lines.iter().enumerate() {
+            for cell in line.cells() {
+                through[cell].push(index);
+            }
+        }
+
+        Self {
+            board: Bitboard::first(cell_count),
+            lines,
+            first_cells,
+            through,
+        }
+    }
+
+    /// Masks for `config`, computed once per board shape
+    pub fn for_config(config: &BoardConfig) -> Rc<LineMasks> {
+        LINE_MASKS.with(|cache| {
+            cache
+                .borrow_mut()
+                .entry(*config)
+                .or_insert_with(|| Rc::new(Self::new(config)))
+                .clone()
+        })
+    }
+
+    /// Lines passing through `cell`
+    pub fn through(&self, cell: usize) -> impl Iterator<Item = &Bitboard> + '_ {
+        self.through[cell].iter().map(|&index| &self.lines[index])
+    }
+}
+
+/// Stones of both sides
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BitPosition {
+    pub x: Bitboard,
+    pub o: Bitboard,
+}
+
+impl BitPosition {
+    pub fn from_cells(cells: &[Option<Player>]) -> Self {
+        let mut position = Self::default();
+        for (cell, symbol) in cells.iter().enumerate() {
+            if let Some(symbol) = symbol {
+                position.place(cell, *symbol);
+            }
+        }
+        position
+    }
+
+    pub fn side(&self, symbol: Player) -> Bitboard {
+        match symbol {
+            Player::X => self.x,
+            Player::O => self.o,
+        }
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.x | self.o
+    }
+
+    pub fn symbol_at(&self, cell: usize) -> Option<Player> {
+        if self.x.contains(cell) {
+            Some(Player::X)
+        } else if self.o.contains(cell) {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
+    pub fn place(&mut self, cell: usize, symbol: Player) {
+        match symbol {
+            Player::X => self.x.set(cell),
+            Player::O => self.o.set(cell),
+        }
+    }
+
+    pub fn remove(&mut self, cell: usize) {
+        self.x.clear(cell);
+        self.o.clear(cell);
+    }
+
+    /// Cells still free on the board described by `masks`
+    pub fn empty_cells(&self, masks: &LineMasks) -> Bitboard {
+        masks.board.without(&self.occupied())
+    }
+
+    pub fn is_full(&self, masks: &LineMasks) -> bool {
+        self.occupied().contains_all(&masks.board)
+    }
+
+    /// A completed line and the symbol holding it
+    pub fn winning_line<'a>(&self, masks: &'a LineMasks) -> Option<(Player, &'a Bitboard)> {
+        masks
+            .lines
+            .iter()
+            .zip(&masks.first_cells)
+            .find_map(|(line, &first)| {
+                let symbol = self.symbol_at(first)?;
+                self.side(symbol).contains_all(line).then_some((symbol, line))
+            })
+    }
+
+    /// Whether `symbol` playing `cell` would complete a line
+    pub fn completes_line(&self, masks: &LineMasks, cell: usize, symbol: Player) -> bool {
+        let mut side = self.side(symbol);
+        side.set(cell);
+        masks.through(cell).any(|line| side.contains_all(line))
+    }
+
+    /// Whether some line holds at most one of the two symbols
+    pub fn has_open_line(&self, masks: &LineMasks) -> bool {
+        masks
+            .lines
+            .iter()
+            .any(|line| !(self.x.intersects(line) && self.o.intersects(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_round_trip_across_words() {
+        let cells = [0, 63, 64, 200, 360];
+        let board: Bitboard = cells.into_iter().collect();
+
+        assert_eq!(board.count(), 5);
+        assert_eq!(board.cells().collect::<Vec<_>>(), cells);
+        assert!(board.contains(64) && !board.contains(65));
+    }
+
+    #[test]
+    fn test_first_covers_exactly_the_board() {
+        let board = Bitboard::first(19 * 19);
+
+        assert_eq!(board.count(), 361);
+        assert!(board.contains(360) && !board.contains(361));
+        assert_eq!(Bitboard::first(64).count(), 64);
+    }
+
+    #[test]
+    fn test_masks_match_winning_lines() {
+        let config = BoardConfig::new(15, 15, 5).unwrap();
+        let masks = LineMasks::new(&config);
+        let lines = config.winning_lines();
+
+        assert_eq!(masks.lines.len(), lines.len());
+        for (mask, line) in masks.lines.iter().zip(&lines) {
+            assert_eq!(mask.cells().collect::<Vec<_>>(), *line);
+        }
+    }
+
+    #[test]
+    fn test_win_detection() {
+        let config = BoardConfig::default();
+        let masks = LineMasks::for_config(&config);
+        let mut position = BitPosition::default();
+        position.place(2, Player::O);
+        position.place(4, Player::O);
+
+        assert!(position.completes_line(&masks, 6, Player::O));
+        assert!(!position.completes_line(&masks, 6, Player::X));
+        assert_eq!(position.winning_line(&masks), None);
+
+        position.place(6, Player::O);
+        let (symbol, line) = position.winning_line(&masks).unwrap();
+        assert_eq!(symbol, Player::O);
+        assert_eq!(line.cells().collect::<Vec<_>>(), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn test_open_lines_and_full_board() {
+        let masks = LineMasks::for_config(&BoardConfig::default());
+        use Player::{O, X};
+        let cells = [X, O, X, X, O, O, O, X, X].map(Some);
+        let position = BitPosition::from_cells(&cells);
+
+        assert!(position.is_full(&masks));
+        assert!(!position.has_open_line(&masks));
+        assert!(position.empty_cells(&masks).is_empty());
+        assert_eq!(position.symbol_at(1), Some(O));
+    }
+}
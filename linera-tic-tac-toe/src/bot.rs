@@ -0,0 +1,184 @@
+//! On-chain computer opponent for classic 3x3 games.
+//!
+//! Contracts must be deterministic, so the bot's "random" choices are drawn
+//! from a hash of the game id, the move number and the block timestamp.
+
+use crate::solver::Solver;
+use crate::{Game, GameStatus, Move, PlayerId, TicTacToeError};
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Player id taken by the bot in the second seat of a bot game
+pub const BOT_PLAYER_ID: &str = "bot";
+
+/// How well the bot plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Any legal move
+    Easy,
+    /// Perfect play two moves out of three, a random move otherwise
+    Medium,
+    /// Perfect play, the bot never loses
+    Hard,
+}
+
+impl Difficulty {
+    /// Display name of the bot
+    pub fn bot_name(self) -> String {
+        format!("Bot ({:?})", self)
+    }
+}
+
+/// Whether `player_id` is the bot's seat
+pub fn is_bot(player_id: &PlayerId) -> bool {
+    player_id == BOT_PLAYER_ID
+}
+
+/// Play the bot's reply if it is the bot's turn in a bot game
+pub fn reply(game: &mut Game, timestamp: Timestamp) -> Result<(), TicTacToeError> {
+    let Some(difficulty) = game.bot else {
+        return Ok(());
+    };
+    if game.status != GameStatus::InProgress
+        || !game.player_to_move().is_some_and(|player| is_bot(&player.id))
+    {
+        return Ok(());
+    }
+
+    let seed = seed(game, timestamp);
+    let position = choose_position(game, difficulty, seed)?;
+    game.play(&BOT_PLAYER_ID.to_string(), Move::at(position), timestamp)
+}
+
+/// Cell the bot plays in the current position
+pub fn choose_position(
+    game: &Game,
+    difficulty: Difficulty,
+    seed: u64,
+) -> Result<usize, TicTacToeError> {
+    let mut solver = if game.options.misere {
+        Solver::misere()
+    } else {
+        Solver::new()
+    };
+    let solution = solver.solve(game)?;
+
+    let empty: Vec<usize> = (0..game.board.len())
+        .filter(|&position| game.board[position].is_none())
+        .collect();
+    let candidates = match difficulty {
+        Difficulty::Easy => empty,
+        Difficulty::Medium if seed.is_multiple_of(3) => empty,
+        Difficulty::Medium | Difficulty::Hard => solution.best_moves,
+    };
+
+    // Finished positions never reach here: `reply` only plays in-progress games
+    candidates
+        .get((seed >> 8) as usize % candidates.len().max(1))
+        .copied()
+        .ok_or(TicTacToeError::GameNotInProgress)
+}
+
+/// Deterministic seed for the bot's next move
+fn seed(game: &Game, timestamp: Timestamp) -> u64 {
+    let mut state = game.id ^ (game.moves.len() as u64).rotate_left(32) ^ timestamp.micros();
+    splitmix64(&mut state)
+}
+
+/// SplitMix64 step, a small well-mixed hash for seeding choices
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Player;
+
+    fn bot_game(difficulty: Difficulty) -> Game {
+        Game::against_bot(
+            7,
+            "alice".to_string(),
+            "Alice".to_string(),
+            difficulty,
+            Timestamp::from(0),
+        )
+    }
+
+    /// Alice plays the lowest empty cell, the bot answers
+    fn play_out(game: &mut Game) {
+        let mut clock = 1;
+        while game.status == GameStatus::InProgress {
+            let position = game.board.iter().position(|cell| cell.is_none()).unwrap();
+            game.make_move(&"alice".to_string(), position, Timestamp::from(clock))
+                .unwrap();
+            reply(game, Timestamp::from(clock)).unwrap();
+            clock += 1;
+        }
+    }
+
+    #[test]
+    fn test_bot_takes_the_second_seat() {
+        let game = bot_game(Difficulty::Easy);
+
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert_eq!(game.players[1].id, BOT_PLAYER_ID);
+        assert_eq!(game.players[1].symbol, Player::O);
+        assert_eq!(game.players[1].name, "Bot (Easy)");
+    }
+
+    #[test]
+    fn test_bot_replies_on_its_turn_only() {
+        let mut game = bot_game(Difficulty::Easy);
+
+        reply(&mut game, Timestamp::from(1)).unwrap();
+        assert!(game.moves.is_empty());
+
+        game.make_move(&"alice".to_string(), 4, Timestamp::from(1)).unwrap();
+        reply(&mut game, Timestamp::from(1)).unwrap();
+        assert_eq!(game.moves.len(), 2);
+        assert!(is_bot(&game.moves[1].player_id));
+        assert_eq!(game.current_player, Player::X);
+    }
+
+    #[test]
+    fn test_hard_bot_never_loses() {
+        let mut game = bot_game(Difficulty::Hard);
+        play_out(&mut game);
+
+        assert_ne!(game.winner_id.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_hard_bot_answers_corner_in_center() {
+        let mut game = bot_game(Difficulty::Hard);
+        game.make_move(&"alice".to_string(), 0, Timestamp::from(1)).unwrap();
+
+        assert_eq!(choose_position(&game, Difficulty::Hard, 12345).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_easy_bot_plays_legal_moves() {
+        for seed in 0..20 {
+            let mut game = bot_game(Difficulty::Easy);
+            game.make_move(&"alice".to_string(), 4, Timestamp::from(1)).unwrap();
+
+            let position = choose_position(&game, Difficulty::Easy, seed).unwrap();
+            assert!(game.board[position].is_none());
+        }
+    }
+
+    #[test]
+    fn test_bot_choice_is_deterministic() {
+        let mut first = bot_game(Difficulty::Medium);
+        let mut second = bot_game(Difficulty::Medium);
+        play_out(&mut first);
+        play_out(&mut second);
+
+        assert_eq!(first.board, second.board);
+    }
+}
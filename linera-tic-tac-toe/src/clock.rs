@@ -0,0 +1,155 @@
+//! Chess-style game clocks.
+//!
+//! Each side starts with the base time and gains the increment after every
+//! move it makes. Time is measured between block timestamps: the clock of
+//! the side to move runs from the moment its turn began, and nobody has to
+//! be online to stop it, so a waiting player can claim the game once it hits
+//! zero.
+
+use crate::{Player, TicTacToeError};
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Time allowed to each player, chosen when the game is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    /// Starting time of each player, in microseconds
+    pub base_micros: u64,
+    /// Time added to a player's clock after each of their moves, in microseconds
+    pub increment_micros: u64,
+}
+
+impl TimeControl {
+    /// A time control of `base_secs` per player plus `increment_secs` per move
+    pub fn new(base_secs: u64, increment_secs: u64) -> Result<Self, TicTacToeError> {
+        let control = Self {
+            base_micros: base_secs.saturating_mul(1_000_000),
+            increment_micros: increment_secs.saturating_mul(1_000_000),
+        };
+        control.validate()?;
+        Ok(control)
+    }
+
+    /// Reject controls that would flag a player before the first move
+    pub fn validate(&self) -> Result<(), TicTacToeError> {
+        if self.base_micros == 0 {
+            return Err(TicTacToeError::InvalidTimeControl);
+        }
+        Ok(())
+    }
+}
+
+/// Remaining time of both players
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameClock {
+    pub control: TimeControl,
+    /// Time X had left when its clock last stopped
+    pub x_remaining_micros: u64,
+    /// Time O had left when its clock last stopped
+    pub o_remaining_micros: u64,
+    /// When the side to move started its turn, `None` while the clock is stopped
+    pub turn_started_at: Option<Timestamp>,
+}
+
+impl GameClock {
+    /// A stopped clock with the base time on both sides
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            x_remaining_micros: control.base_micros,
+            o_remaining_micros: control.base_micros,
+            turn_started_at: None,
+        }
+    }
+
+    /// Start the clock of the side to move
+    pub fn start(&mut self, now: Timestamp) {
+        self.turn_started_at = Some(now);
+    }
+
+    /// Stop the clock for good, charging `to_move` for its unfinished turn
+    pub fn stop(&mut self, to_move: Player, now: Timestamp) {
+        let remaining = self.remaining(to_move, to_move, now);
+        *self.stored_mut(to_move) = remaining;
+        self.turn_started_at = None;
+    }
+
+    /// Time `symbol` has left at `now`, given that `to_move` is on turn
+    pub fn remaining(&self, symbol: Player, to_move: Player, now: Timestamp) -> u64 {
+        let stored = match symbol {
+            Player::X => self.x_remaining_micros,
+            Player::O => self.o_remaining_micros,
+        };
+        match self.turn_started_at {
+            Some(started) if symbol == to_move => {
+                stored.saturating_sub(now.micros().saturating_sub(started.micros()))
+            }
+            _ => stored,
+        }
+    }
+
+    /// Whether the side to move has run out of time
+    pub fn is_flagged(&self, to_move: Player, now: Timestamp) -> bool {
+        self.turn_started_at.is_some() && self.remaining(to_move, to_move, now) == 0
+    }
+
+    /// Stop the mover's clock after a move, add the increment and start the
+    /// opponent's turn
+    pub fn punch(&mut self, mover: Player, now: Timestamp) {
+        let remaining = self.remaining(mover, mover, now);
+        *self.stored_mut(mover) = remaining.saturating_add(self.control.increment_micros);
+        self.turn_started_at = Some(now);
+    }
+
+    fn stored_mut(&mut self, symbol: Player) -> &mut u64 {
+        match symbol {
+            Player::X => &mut self.x_remaining_micros,
+            Player::O => &mut self.o_remaining_micros,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Timestamp {
+        Timestamp::from(secs * 1_000_000)
+    }
+
+    #[test]
+    fn test_only_the_side_to_move_loses_time() {
+        let mut clock = GameClock::new(TimeControl::new(60, 0).unwrap());
+        clock.start(secs(100));
+
+        assert_eq!(clock.remaining(Player::X, Player::X, secs(110)), 50_000_000);
+        assert_eq!(clock.remaining(Player::O, Player::X, secs(110)), 60_000_000);
+        assert!(!clock.is_flagged(Player::X, secs(159)));
+        assert!(clock.is_flagged(Player::X, secs(160)));
+    }
+
+    #[test]
+    fn test_moves_add_the_increment() {
+        let mut clock = GameClock::new(TimeControl::new(60, 5).unwrap());
+        clock.start(secs(0));
+        clock.punch(Player::X, secs(20));
+
+        assert_eq!(clock.x_remaining_micros, 45_000_000);
+        assert_eq!(clock.remaining(Player::O, Player::O, secs(30)), 50_000_000);
+    }
+
+    #[test]
+    fn test_stopped_clock_keeps_its_time() {
+        let mut clock = GameClock::new(TimeControl::new(60, 0).unwrap());
+        clock.start(secs(0));
+        clock.stop(Player::X, secs(15));
+
+        assert_eq!(clock.remaining(Player::X, Player::X, secs(1000)), 45_000_000);
+        assert!(!clock.is_flagged(Player::X, secs(1000)));
+    }
+
+    #[test]
+    fn test_zero_base_time_is_rejected() {
+        assert!(matches!(TimeControl::new(0, 10), Err(TicTacToeError::InvalidTimeControl)));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    ApplicationState, Game, GameId, Message, Operation, PlayerId, Query, QueryResponse,
-    TicTacToeError,
+    ApplicationState, BoardConfig, Game, GameId, GameSettings, Message, Move, Operation, PlayerId,
+    Query, QueryResponse, TicTacToeError,
 };
 use async_trait::async_trait;
 use linera_base::{
@@ -37,14 +37,37 @@ impl linera_execution::Contract for TicTacToeContract {
         operation: Operation,
     ) -> ExecutionResult<Self::Message> {
         let timestamp = context.execution_state_view.system.timestamp.get();
+
         let mut state = ApplicationState::load(context.execution_state_view.context().clone())
             .await
             .map_err(|e| ExecutionResult::system_error(format!("Failed to load state: {}", e)))?;
 
+        // Players act as the chain owner that signed the operation, or through
+        // a session key they authorized
+        let signer = context.authenticated_signer.map(|owner| owner.to_string());
+        state
+            .authorize(&operation, signer.as_ref(), timestamp)
+            .await
+            .map_err(Self::game_action_error)?;
+
         match operation {
-            Operation::CreateGame { player_id, player_name } => {
+            Operation::CreateGame {
+                player_id,
+                player_name,
+                width,
+                height,
+                win_length,
+                variant,
+                options,
+                time_control,
+                visibility,
+            } => {
+                let settings =
+                    GameSettings::new(variant, width, height, win_length, options, time_control)
+                        .map_err(|e| ExecutionResult::user_error(e.to_string()))?;
+
                 let game_id = state
-                    .create_game(player_id.clone(), player_name, timestamp)
+                    .create_game(player_id.clone(), player_name, settings, visibility, timestamp)
                     .await
                     .map_err(|e| ExecutionResult::system_error(format!("Failed to create game: {}", e)))?;
 
@@ -68,9 +91,10 @@ impl linera_execution::Contract for TicTacToeContract {
                 game_id,
                 player_id,
                 player_name,
+                join_code,
             } => {
                 state
-                    .join_game(game_id, player_id.clone(), player_name)
+                    .join_game(game_id, player_id.clone(), player_name, join_code, timestamp)
                     .await
                     .map_err(|e| match e {
                         TicTacToeError::GameNotFound => {
@@ -79,6 +103,11 @@ impl linera_execution::Contract for TicTacToeContract {
                         TicTacToeError::GameFull => {
                             ExecutionResult::user_error("Game is full".to_string())
                         }
+                        TicTacToeError::AlreadyInGame
+                        | TicTacToeError::NotInvited
+                        | TicTacToeError::BlockedByPlayer => {
+                            ExecutionResult::user_error(e.to_string())
+                        }
                         _ => ExecutionResult::system_error(format!("Failed to join game: {}", e)),
                     })?;
 
@@ -102,40 +131,266 @@ impl linera_execution::Contract for TicTacToeContract {
                 game_id,
                 player_id,
                 position,
+                sub_board,
+                symbol,
+            } => {
+                let mv = Move {
+                    position,
+                    sub_board,
+                    symbol,
+                };
+                Self::execute_move(&mut state, game_id, player_id, mv, timestamp).await
+            }
+
+            Operation::MakeMoveAt {
+                game_id,
+                player_id,
+                x,
+                y,
+                z,
+                symbol,
             } => {
                 let game = state
-                    .make_move(game_id, player_id.clone(), position, timestamp)
+                    .get_game(game_id)
                     .await
-                    .map_err(|e| match e {
-                        TicTacToeError::GameNotFound => {
-                            ExecutionResult::user_error("Game not found".to_string())
-                        }
-                        TicTacToeError::InvalidMove(pos) => {
-                            ExecutionResult::user_error(format!("Invalid move at position {}", pos))
-                        }
-                        TicTacToeError::NotYourTurn => {
-                            ExecutionResult::user_error("Not your turn".to_string())
-                        }
-                        TicTacToeError::GameNotInProgress => {
-                            ExecutionResult::user_error("Game is not in progress".to_string())
-                        }
-                        TicTacToeError::PlayerNotInGame => {
-                            ExecutionResult::user_error("Player not in this game".to_string())
-                        }
-                        TicTacToeError::InvalidPosition(pos) => {
-                            ExecutionResult::user_error(format!("Invalid position: {}", pos))
-                        }
-                        _ => ExecutionResult::system_error(format!("Failed to make move: {}", e)),
-                    })?;
+                    .map_err(|e| ExecutionResult::system_error(format!("Failed to get game: {}", e)))?
+                    .ok_or_else(|| ExecutionResult::user_error("Game not found".to_string()))?;
+
+                let mv = game.move_at(x, y, z).map_err(Self::game_action_error)?;
+                let mv = Move { symbol, ..mv };
+                Self::execute_move(&mut state, game_id, player_id, mv, timestamp).await
+            }
+
+            Operation::RequestTakeback { game_id, player_id } => {
+                let game = state
+                    .request_takeback(game_id, player_id)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game_id, game).await
+            }
+
+            Operation::AcceptTakeback { game_id, player_id } => {
+                let game = state
+                    .accept_takeback(game_id, player_id)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game_id, game).await
+            }
+
+            Operation::CreateBotGame {
+                player_id,
+                player_name,
+                difficulty,
+            } => {
+                let game_id = state
+                    .create_bot_game(player_id, player_name, difficulty, timestamp)
+                    .await
+                    .map_err(|e| ExecutionResult::system_error(format!("Failed to create game: {}", e)))?;
+
+                let game = state
+                    .get_game(game_id)
+                    .await
+                    .map_err(|e| ExecutionResult::system_error(format!("Failed to get game: {}", e)))?
+                    .ok_or_else(|| ExecutionResult::system_error("Game not found after creation".to_string()))?;
+
+                Self::publish_game(&mut state, game_id, game).await
+            }
+
+            Operation::ClaimTimeout { game_id, player_id } => {
+                let (game, next) = state
+                    .claim_timeout(game_id, player_id, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, std::iter::once(game).chain(next)).await
+            }
+
+            Operation::Resign { game_id, player_id } => {
+                let (game, next) = state
+                    .resign(game_id, player_id, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, std::iter::once(game).chain(next)).await
+            }
+
+            Operation::OfferDraw { game_id, player_id } => {
+                let game = state
+                    .offer_draw(game_id, player_id)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game_id, game).await
+            }
+
+            Operation::RespondToDraw {
+                game_id,
+                player_id,
+                accept,
+            } => {
+                let (game, next) = state
+                    .respond_to_draw(game_id, player_id, accept, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, std::iter::once(game).chain(next)).await
+            }
+
+            Operation::OfferRematch { game_id, player_id } => {
+                let (game, rematch) = state
+                    .offer_rematch(game_id, player_id, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, std::iter::once(game).chain(rematch)).await
+            }
+
+            Operation::AcceptRematch { game_id, player_id } => {
+                let (game, rematch) = state
+                    .accept_rematch(game_id, player_id, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, [game, rematch]).await
+            }
+
+            Operation::CreateMatch {
+                player_id,
+                player_name,
+                best_of,
+                width,
+                height,
+                win_length,
+                variant,
+                options,
+                time_control,
+            } => {
+                let settings =
+                    GameSettings::new(variant, width, height, win_length, options, time_control)
+                        .map_err(|e| ExecutionResult::user_error(e.to_string()))?;
 
                 state
-                    .flush()
+                    .create_match(player_id, player_name, best_of, settings, timestamp)
                     .await
-                    .map_err(|e| ExecutionResult::system_error(format!("Failed to flush state: {}", e)))?;
+                    .map_err(Self::game_action_error)?;
 
-                // Send message to notify about move
-                let message = Message::GameUpdate { game_id, game };
-                ExecutionResult::default().with_message(message)
+                // No game exists until an opponent finds the match among the
+                // service's open matches and joins it
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::JoinMatch {
+                match_id,
+                player_id,
+                player_name,
+            } => {
+                let (_, game) = state
+                    .join_match(match_id, player_id, player_name, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game.id, game).await
+            }
+
+            Operation::RegisterProfile {
+                player_id,
+                handle,
+                display_name,
+                avatar_hash,
+            } => {
+                state
+                    .register_profile(player_id, handle, display_name, avatar_hash, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::RenameProfile { player_id, handle } => {
+                state
+                    .rename_profile(player_id, handle)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::UpdateProfile {
+                player_id,
+                display_name,
+                avatar_hash,
+            } => {
+                // Games pick up the new name when read, so none are republished
+                state
+                    .update_profile(player_id, display_name, avatar_hash)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::AuthorizeSessionKey {
+                player_id,
+                key,
+                expires_at,
+                scope,
+            } => {
+                state
+                    .authorize_session_key(player_id, key, expires_at, scope, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::RevokeSessionKey { player_id, key } => {
+                state
+                    .revoke_session_key(&player_id, &key)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::Follow { player_id, target } => {
+                state.follow(player_id, target).await.map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::Unfollow { player_id, target } => {
+                state.unfollow(player_id, target).await.map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::Block { player_id, target } => {
+                state.block(player_id, target).await.map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::Unblock { player_id, target } => {
+                state.unblock(player_id, target).await.map_err(Self::game_action_error)?;
+                Self::publish_games(&mut state, []).await
+            }
+
+            Operation::Challenge {
+                player_id,
+                player_name,
+                opponent,
+                rules,
+            } => {
+                let game = state
+                    .challenge(player_id, player_name, opponent, rules, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game.id, game).await
+            }
+
+            Operation::AcceptChallenge {
+                game_id,
+                player_id,
+                player_name,
+            } => {
+                let game = state
+                    .accept_challenge(game_id, player_id, player_name, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game_id, game).await
+            }
+
+            Operation::DeclineChallenge { game_id, player_id } => {
+                let game = state
+                    .decline_challenge(game_id, player_id, timestamp)
+                    .await
+                    .map_err(Self::game_action_error)?;
+                Self::publish_game(&mut state, game_id, game).await
             }
         }
     }
@@ -200,17 +455,17 @@ impl linera_execution::Service for TicTacToeService {
             .map_err(|e| format!("Failed to load state: {}", e))?;
 
         match query {
-            Query::GetGame { game_id } => {
+            Query::GetGame { game_id, viewer } => {
                 let game = state
-                    .get_game(game_id)
+                    .get_game_for(game_id, viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get game: {}", e))?;
                 Ok(QueryResponse::Game(game))
             }
 
-            Query::GetAllGames => {
+            Query::GetAllGames { viewer } => {
                 let games = state
-                    .get_all_games()
+                    .get_visible_games(viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get games: {}", e))?;
                 Ok(QueryResponse::Games(games))
@@ -229,9 +484,105 @@ impl linera_execution::Service for TicTacToeService {
 
 // Helper functions for contract operations
 impl TicTacToeContract {
-    /// Validate that a position is within bounds
-    fn validate_position(position: usize) -> Result<(), TicTacToeError> {
-        if position >= 9 {
+    /// Play a move and notify about the updated game, and about the next game
+    /// when the move ends a match game
+    async fn execute_move(
+        state: &mut ApplicationState,
+        game_id: GameId,
+        player_id: PlayerId,
+        mv: Move,
+        timestamp: Timestamp,
+    ) -> ExecutionResult<Message> {
+        let (game, next) = state
+            .make_move(game_id, player_id.clone(), mv, timestamp)
+            .await
+            .map_err(|e| match e {
+                TicTacToeError::GameNotFound => {
+                    ExecutionResult::user_error("Game not found".to_string())
+                }
+                TicTacToeError::InvalidMove(pos) => {
+                    ExecutionResult::user_error(format!("Invalid move at position {}", pos))
+                }
+                TicTacToeError::NotYourTurn => {
+                    ExecutionResult::user_error("Not your turn".to_string())
+                }
+                TicTacToeError::GameNotInProgress => {
+                    ExecutionResult::user_error("Game is not in progress".to_string())
+                }
+                TicTacToeError::PlayerNotInGame => {
+                    ExecutionResult::user_error("Player not in this game".to_string())
+                }
+                TicTacToeError::InvalidPosition(pos) => {
+                    ExecutionResult::user_error(format!("Invalid position: {}", pos))
+                }
+                TicTacToeError::MissingSubBoard
+                | TicTacToeError::InvalidSubBoard(_)
+                | TicTacToeError::MustPlayInBoard(_)
+                | TicTacToeError::SubBoardFinished(_)
+                | TicTacToeError::SymbolNotAllowed
+                | TicTacToeError::ColumnFull(_)
+                | TicTacToeError::OutOfTime => {
+                    ExecutionResult::user_error(e.to_string())
+                }
+                _ => ExecutionResult::system_error(format!("Failed to make move: {}", e)),
+            })?;
+
+        Self::publish_games(state, std::iter::once(game).chain(next)).await
+    }
+
+    /// Report a rejected game action to the caller
+    fn game_action_error(error: TicTacToeError) -> ExecutionResult<Message> {
+        ExecutionResult::user_error(error.to_string())
+    }
+
+    /// Persist the state and notify about the updated game, showing players
+    /// under their profile names as queries do
+    async fn publish_game(
+        state: &mut ApplicationState,
+        game_id: GameId,
+        game: Game,
+    ) -> ExecutionResult<Message> {
+        state
+            .flush()
+            .await
+            .map_err(|e| ExecutionResult::system_error(format!("Failed to flush state: {}", e)))?;
+
+        let game = state
+            .with_profile_names(game)
+            .await
+            .map_err(|e| ExecutionResult::system_error(format!("Failed to load profiles: {}", e)))?;
+        let message = Message::GameUpdate { game_id, game };
+        ExecutionResult::default().with_message(message)
+    }
+
+    /// Persist the state and notify about each updated game, showing players
+    /// under their profile names as queries do
+    async fn publish_games(
+        state: &mut ApplicationState,
+        games: impl IntoIterator<Item = Game>,
+    ) -> ExecutionResult<Message> {
+        state
+            .flush()
+            .await
+            .map_err(|e| ExecutionResult::system_error(format!("Failed to flush state: {}", e)))?;
+
+        let mut result = ExecutionResult::default();
+        for game in games {
+            let game = state
+                .with_profile_names(game)
+                .await
+                .map_err(|e| ExecutionResult::system_error(format!("Failed to load profiles: {}", e)))?;
+            result = result.with_message(Message::GameUpdate {
+                game_id: game.id,
+                game,
+            });
+        }
+        result
+    }
+
+    /// Validate that a position is within the bounds of the board
+    fn validate_position(config: &BoardConfig, position: usize) -> Result<(), TicTacToeError> {
+        if position >= config.cell_count() {
             Err(TicTacToeError::InvalidPosition(position))
         } else {
             Ok(())
@@ -262,10 +613,15 @@ mod tests {
 
     #[test]
     fn test_validate_position() {
-        assert!(TicTacToeContract::validate_position(0).is_ok());
-        assert!(TicTacToeContract::validate_position(8).is_ok());
-        assert!(TicTacToeContract::validate_position(9).is_err());
-        assert!(TicTacToeContract::validate_position(100).is_err());
+        let classic = BoardConfig::default();
+        assert!(TicTacToeContract::validate_position(&classic, 0).is_ok());
+        assert!(TicTacToeContract::validate_position(&classic, 8).is_ok());
+        assert!(TicTacToeContract::validate_position(&classic, 9).is_err());
+        assert!(TicTacToeContract::validate_position(&classic, 100).is_err());
+
+        let gomoku = BoardConfig::new(15, 15, 5).unwrap();
+        assert!(TicTacToeContract::validate_position(&gomoku, 224).is_ok());
+        assert!(TicTacToeContract::validate_position(&gomoku, 225).is_err());
     }
 
     #[tokio::test]
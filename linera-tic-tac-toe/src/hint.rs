@@ -0,0 +1,499 @@
+//! Move suggestions for the side to move.
+//!
+//! Classic 3x3 positions are solved exactly. Every other board is ranked by a
+//! heuristic that looks for immediate wins, blocks, forks and replies that
+//! would hand the opponent the game, then prefers cells on open lines.
+
+use crate::solver::{Solver, Value};
+use crate::{
+    Game, GameRules, GameStatus, Move, Outcome, Player, PlayerId, TicTacToeError, UltimateBoard,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Why a move is suggested, or avoided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HintReason {
+    WinsGame,
+    CreatesFork,
+    BlocksLine,
+    MakesThreat,
+    ExtendsLine,
+    WinsWithPerfectPlay,
+    HoldsDraw,
+    EndsInDraw,
+    LosesWithPerfectPlay,
+    GivesOpponentWin,
+    LosesGame,
+}
+
+impl fmt::Display for HintReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            HintReason::WinsGame => "wins the game",
+            HintReason::CreatesFork => "creates a fork",
+            HintReason::BlocksLine => "blocks opponent's line",
+            HintReason::MakesThreat => "threatens to complete a line",
+            HintReason::ExtendsLine => "builds on open lines",
+            HintReason::WinsWithPerfectPlay => "wins with perfect play",
+            HintReason::HoldsDraw => "holds the draw",
+            HintReason::EndsInDraw => "ends the game in a draw",
+            HintReason::LosesWithPerfectPlay => "loses against perfect play",
+            HintReason::GivesOpponentWin => "lets the opponent win next move",
+            HintReason::LosesGame => "completes a line and loses",
+        };
+        f.write_str(text)
+    }
+}
+
+/// A suggested move with its evaluation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hint {
+    pub mv: Move,
+    /// Higher is better; only the order is meaningful
+    pub score: i64,
+    pub reason: HintReason,
+    /// Whether the score comes from an exact solve rather than the heuristic
+    pub exact: bool,
+}
+
+/// Score bands, far enough apart that line-building never outweighs tactics
+const WIN_SCORE: i64 = 1_000_000;
+const FORK_SCORE: i64 = 10_000;
+const BLOCK_SCORE: i64 = 5_000;
+const THREAT_SCORE: i64 = 1_000;
+/// Added per solved value step so exact results dominate the heuristic
+const SOLVED_STEP: i64 = 10 * WIN_SCORE;
+
+/// Suggested moves for `player_id`, best first; the game is left untouched
+pub fn hints(game: &Game, player_id: &PlayerId) -> Result<Vec<Hint>, TicTacToeError> {
+    if game.status != GameStatus::InProgress {
+        return Err(TicTacToeError::GameNotInProgress);
+    }
+    let player = game
+        .players
+        .iter()
+        .find(|p| p.id == *player_id)
+        .ok_or(TicTacToeError::PlayerNotInGame)?;
+    if player.symbol != game.current_player {
+        return Err(TicTacToeError::NotYourTurn);
+    }
+
+    let rules = game.variant.rules();
+    let lines = rules.board_lines(game);
+    let mut lines_through = vec![Vec::new(); game.board.len()];
+    for (id, line) in lines.iter().enumerate() {
+        for &cell in line {
+            lines_through[cell].push(id);
+        }
+    }
+    let mut position = Position {
+        game,
+        lines: &lines,
+        lines_through: &lines_through,
+        me: player.symbol,
+        opponent_threats: BTreeSet::new(),
+    };
+    position.opponent_threats = position.threats(&game.board, player.symbol.opponent());
+
+    let solved = solved_values(game);
+    let mut scratch = Scratch::new(game);
+    let mut hints: Vec<Hint> = rules
+        .legal_moves(game)
+        .into_iter()
+        .map(|mv| {
+            let (mut score, mut reason) = position.evaluate(&mut scratch, &mv);
+            let value = solved.as_ref().map(|values| values[&mv.position]);
+            if let Some(value) = value {
+                score += SOLVED_STEP * value as i64;
+                reason = match (value, reason) {
+                    (Value::Loss, _) => HintReason::LosesWithPerfectPlay,
+                    (Value::Win, HintReason::ExtendsLine) => HintReason::WinsWithPerfectPlay,
+                    (Value::Draw, HintReason::ExtendsLine) => HintReason::HoldsDraw,
+                    (_, reason) => reason,
+                };
+            }
+            Hint {
+                mv,
+                score,
+                reason,
+                exact: value.is_some(),
+            }
+        })
+        .collect();
+
+    hints.sort_by_key(|hint| Reverse(hint.score));
+    Ok(hints)
+}
+
+/// Exact value of each move when the position is small enough to solve
+fn solved_values(game: &Game) -> Option<HashMap<usize, Value>> {
+    let mut solver = if game.options.misere {
+        Solver::misere()
+    } else {
+        Solver::new()
+    };
+    solver.solve(game).ok()?;
+
+    let mut board = [None; 9];
+    board.copy_from_slice(&game.board);
+    Some(
+        solver
+            .move_values(&board, game.current_player)
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// How a tried move would end the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ending {
+    Won(Player),
+    Drawn,
+}
+
+/// A move played on the scratch board, with what it takes to undo it
+struct Tried {
+    index: usize,
+    ending: Option<Ending>,
+    ultimate: Option<UltimateBoard>,
+    current_player: Player,
+}
+
+/// A single copy of the game that moves are played on and taken back, so
+/// trying every legal move never copies the game again
+struct Scratch {
+    rules: Box<dyn GameRules>,
+    game: Game,
+}
+
+impl Scratch {
+    fn new(game: &Game) -> Self {
+        Self {
+            rules: game.variant.rules(),
+            game: game.clone(),
+        }
+    }
+
+    /// Play `mv` for the side to move and judge it as `Game::play` would, or
+    /// return `None` if it is not legal
+    fn play(&mut self, mv: &Move) -> Option<Tried> {
+        let game = &mut self.game;
+        let mover = game.current_player;
+        if !game.options.wild && mv.mark(mover) != mover {
+            return None;
+        }
+        self.rules.validate_move(game, mv).ok()?;
+
+        let ultimate = game.ultimate.clone();
+        let index = self.rules.apply_move(game, &mv.with_symbol(mv.mark(mover)));
+        let ending = match self.rules.outcome(game) {
+            // Whoever completes a line wins it, and loses it under misère rules
+            Some(Outcome::Win { .. }) if game.options.misere => Some(Ending::Won(mover.opponent())),
+            Some(Outcome::Win { .. }) => Some(Ending::Won(mover)),
+            Some(Outcome::Draw) => Some(Ending::Drawn),
+            None if game.options.early_draw && !self.rules.has_open_line(game) => {
+                Some(Ending::Drawn)
+            }
+            None => {
+                game.current_player = self.rules.next_player(game);
+                None
+            }
+        };
+        Some(Tried {
+            index,
+            ending,
+            ultimate,
+            current_player: mover,
+        })
+    }
+
+    /// Undo a move made with `play`
+    fn take_back(&mut self, tried: Tried) {
+        self.game.board[tried.index] = None;
+        self.game.ultimate = tried.ultimate;
+        self.game.current_player = tried.current_player;
+    }
+}
+
+/// The position being evaluated, with its lines indexed by cell
+struct Position<'a> {
+    game: &'a Game,
+    lines: &'a [Vec<usize>],
+    lines_through: &'a [Vec<usize>],
+    me: Player,
+    /// Cells where the opponent would complete a line before this move
+    opponent_threats: BTreeSet<usize>,
+}
+
+impl Position<'_> {
+    /// Heuristic score and reason for one legal move, tried on `scratch`
+    fn evaluate(&self, scratch: &mut Scratch, mv: &Move) -> (i64, HintReason) {
+        let Some(tried) = scratch.play(mv) else {
+            return (-WIN_SCORE, HintReason::LosesGame);
+        };
+        let result = self.judge(scratch, &tried);
+        scratch.take_back(tried);
+        result
+    }
+
+    /// Score and reason for a move just tried on `scratch`
+    fn judge(&self, scratch: &mut Scratch, tried: &Tried) -> (i64, HintReason) {
+        match tried.ending {
+            Some(Ending::Won(winner)) if winner == self.me => {
+                return (WIN_SCORE, HintReason::WinsGame);
+            }
+            Some(Ending::Won(_)) => return (-WIN_SCORE, HintReason::LosesGame),
+            Some(Ending::Drawn) => return (0, HintReason::EndsInDraw),
+            None => {}
+        }
+
+        let index = tried.index;
+        let misere = self.game.options.misere;
+        let line_score = self.line_score(index);
+        if misere {
+            // Lines are liabilities when completing one loses
+            return (-line_score, HintReason::ExtendsLine);
+        }
+
+        if self.opponent_wins_next(scratch) {
+            return (-WIN_SCORE / 2 + line_score, HintReason::GivesOpponentWin);
+        }
+
+        let board = &scratch.game.board;
+        let blocks = self.opponent_threats.contains(&index);
+        let my_threats = self.threats(board, self.me);
+        let new_threats = self.lines_through[index]
+            .iter()
+            .any(|&line| self.open_cell(board, &self.lines[line], self.me).is_some());
+
+        if new_threats && my_threats.len() >= 2 {
+            (FORK_SCORE + line_score, HintReason::CreatesFork)
+        } else if blocks {
+            (BLOCK_SCORE + line_score, HintReason::BlocksLine)
+        } else if new_threats {
+            (THREAT_SCORE + line_score, HintReason::MakesThreat)
+        } else {
+            (line_score, HintReason::ExtendsLine)
+        }
+    }
+
+    /// Value of the lines through `index` that the opponent has not blocked
+    fn line_score(&self, index: usize) -> i64 {
+        let board = &self.game.board;
+        self.lines_through[index]
+            .iter()
+            .map(|&line| &self.lines[line])
+            .filter(|line| line.iter().all(|&cell| board[cell] != Some(self.me.opponent())))
+            .map(|line| {
+                let mine = line.iter().filter(|&&cell| board[cell] == Some(self.me)).count();
+                4i64.pow(mine as u32)
+            })
+            .sum()
+    }
+
+    /// Empty cells that would complete a line of `symbol`
+    fn threats(&self, board: &[Option<Player>], symbol: Player) -> BTreeSet<usize> {
+        self.lines
+            .iter()
+            .filter_map(|line| self.open_cell(board, line, symbol))
+            .collect()
+    }
+
+    /// The single empty cell of a line otherwise full of `symbol`
+    fn open_cell(&self, board: &[Option<Player>], line: &[usize], symbol: Player) -> Option<usize> {
+        let mut empty = line.iter().filter(|&&cell| board[cell].is_none());
+        let cell = *empty.next()?;
+        let rest_mine = line
+            .iter()
+            .all(|&other| other == cell || board[other] == Some(symbol));
+        (empty.next().is_none() && rest_mine).then_some(cell)
+    }
+
+    /// Whether the side to move on `scratch` can win outright
+    fn opponent_wins_next(&self, scratch: &mut Scratch) -> bool {
+        let opponent = scratch.game.current_player;
+        let threats = self.threats(&scratch.game.board, opponent);
+        if threats.is_empty() {
+            return false;
+        }
+
+        // Only replies landing on a threat can win, so only those are tried
+        let replies: Vec<Move> = scratch
+            .rules
+            .legal_moves(&scratch.game)
+            .into_iter()
+            .filter(|reply| threats.contains(&scratch.rules.target_cell(&scratch.game, reply)))
+            .collect();
+        replies.iter().any(|reply| {
+            let Some(tried) = scratch.play(reply) else {
+                return false;
+            };
+            let wins = tried.ending == Some(Ending::Won(opponent));
+            scratch.take_back(tried);
+            wins
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardConfig, GameVariant};
+    use linera_base::data_types::Timestamp;
+
+    fn game_with(variant: GameVariant, config: BoardConfig, moves: &[Move]) -> Game {
+        let mut game = Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            variant,
+            config,
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        for mv in moves {
+            let mover = game.player_to_move().unwrap().id.clone();
+            game.play(&mover, *mv, Timestamp::from(1)).unwrap();
+        }
+        game
+    }
+
+    fn classic(moves: &[usize]) -> Game {
+        let moves: Vec<Move> = moves.iter().copied().map(Move::at).collect();
+        game_with(GameVariant::Standard, BoardConfig::default(), &moves)
+    }
+
+    #[test]
+    fn test_winning_move_ranks_first() {
+        // X: 0, 1  O: 3, 4
+        let game = classic(&[0, 3, 1, 4]);
+        let hints = hints(&game, &"alice".to_string()).unwrap();
+
+        assert_eq!(hints[0].mv, Move::at(2));
+        assert_eq!(hints[0].reason, HintReason::WinsGame);
+        assert!(hints[0].exact);
+    }
+
+    #[test]
+    fn test_block_is_suggested() {
+        // X: 0, 1  O: 4, so O has to take 2
+        let game = classic(&[0, 4, 1]);
+        let hints = hints(&game, &"bob".to_string()).unwrap();
+
+        assert_eq!(hints[0].mv, Move::at(2));
+        assert_eq!(hints[0].reason, HintReason::BlocksLine);
+        assert!(hints[1..]
+            .iter()
+            .all(|hint| hint.reason == HintReason::LosesWithPerfectPlay));
+    }
+
+    #[test]
+    fn test_hints_leave_the_game_untouched() {
+        let game = classic(&[4]);
+        let before = game.board.clone();
+
+        let hints = hints(&game, &"bob".to_string()).unwrap();
+
+        assert_eq!(game.board, before);
+        assert_eq!(game.moves.len(), 1);
+        assert_eq!(hints.len(), 8);
+    }
+
+    #[test]
+    fn test_only_the_side_to_move_gets_hints() {
+        let game = classic(&[4]);
+
+        assert!(matches!(
+            hints(&game, &"alice".to_string()),
+            Err(TicTacToeError::NotYourTurn)
+        ));
+        assert!(matches!(
+            hints(&game, &"carol".to_string()),
+            Err(TicTacToeError::PlayerNotInGame)
+        ));
+    }
+
+    #[test]
+    fn test_heuristic_finds_win_on_large_board() {
+        let config = BoardConfig::new(5, 5, 3).unwrap();
+        let at = |x: usize, y: usize| Move::at(config.position(x, y, 0).unwrap());
+        let game = game_with(
+            GameVariant::Standard,
+            config,
+            &[at(1, 1), at(4, 4), at(2, 2), at(4, 0)],
+        );
+        // Alice can finish the diagonal at (0,0) or (3,3)
+        let hints = hints(&game, &"alice".to_string()).unwrap();
+
+        assert_eq!(hints[0].reason, HintReason::WinsGame);
+        assert_eq!(hints[1].reason, HintReason::WinsGame);
+        assert!(!hints[0].exact);
+    }
+
+    #[test]
+    fn test_heuristic_finds_fork_on_large_board() {
+        let config = BoardConfig::new(5, 5, 3).unwrap();
+        let at = |x: usize, y: usize| Move::at(config.position(x, y, 0).unwrap());
+        let game = game_with(
+            GameVariant::Standard,
+            config,
+            &[at(0, 0), at(4, 4), at(1, 2), at(4, 0)],
+        );
+        // (1,1) threatens both (2,2) and the column through (1,2)
+        let hints = hints(&game, &"alice".to_string()).unwrap();
+
+        assert_eq!(hints[0].reason, HintReason::CreatesFork);
+        assert!(hints
+            .iter()
+            .any(|hint| hint.mv == at(1, 1) && hint.reason == HintReason::CreatesFork));
+    }
+
+    #[test]
+    fn test_heuristic_flags_moves_that_lose_immediately() {
+        // Gravity: O has three stacked in column 0, X must block the top
+        let moves: Vec<Move> = [1, 0, 2, 0, 6, 0].into_iter().map(Move::at).collect();
+        let config = BoardConfig::default();
+        let game = game_with(GameVariant::Gravity, config, &moves);
+        let hints = hints(&game, &"alice".to_string()).unwrap();
+
+        assert_eq!(hints[0].mv, Move::at(0));
+        assert_eq!(hints[0].reason, HintReason::BlocksLine);
+        assert!(hints[1..]
+            .iter()
+            .all(|hint| hint.reason == HintReason::GivesOpponentWin));
+    }
+
+    #[test]
+    fn test_tried_moves_are_taken_back_completely() {
+        // X: 0, 1 of the centre board; playing 2 there wins it
+        let moves = [(4, 0), (0, 4), (4, 1), (1, 4)].map(|(b, c)| Move::in_sub_board(b, c));
+        let game = game_with(GameVariant::Ultimate, BoardConfig::default(), &moves);
+        let mut scratch = Scratch::new(&game);
+
+        let tried = scratch.play(&Move::in_sub_board(4, 2)).unwrap();
+        assert_eq!(scratch.game.ultimate.as_ref().unwrap().meta_board[4], Some(Player::X));
+        assert_eq!(scratch.game.current_player, Player::O);
+        scratch.take_back(tried);
+
+        assert_eq!(scratch.game.board, game.board);
+        assert_eq!(scratch.game.ultimate, game.ultimate);
+        assert_eq!(scratch.game.current_player, game.current_player);
+        assert!(scratch.play(&Move::in_sub_board(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_ultimate_hints_respect_the_sent_board() {
+        let game = game_with(
+            GameVariant::Ultimate,
+            BoardConfig::default(),
+            &[Move::in_sub_board(4, 2)],
+        );
+        let hints = hints(&game, &"bob".to_string()).unwrap();
+
+        assert_eq!(hints.len(), 9);
+        assert!(hints.iter().all(|hint| hint.mv.sub_board == Some(2)));
+    }
+}
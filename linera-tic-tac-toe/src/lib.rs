@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use linera_base::{
+    crypto::CryptoHash,
     data_types::{ApplicationId, BlockHeight, Timestamp},
     identifiers::{ChainId, MessageId},
 };
@@ -11,6 +12,29 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
+pub mod analysis;
+pub mod bitboard;
+pub mod bot;
+pub mod clock;
+pub mod hint;
+pub mod opening;
+pub mod profile;
+pub mod rules;
+pub mod series;
+pub mod session;
+pub mod social;
+pub mod solver;
+pub mod visibility;
+
+pub use bot::Difficulty;
+pub use clock::{GameClock, TimeControl};
+pub use profile::Profile;
+pub use rules::{GameRules, GameVariant, Move, Outcome, RuleOptions, UltimateBoard};
+pub use series::{Match, MatchId, MatchPlayer};
+pub use session::{SessionKey, SessionScope};
+pub use social::{ChallengeRules, Relations};
+pub use visibility::Visibility;
+
 /// Application state for the Tic Tac Toe game
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TicTacToeState {
@@ -21,11 +45,11 @@ pub struct TicTacToeState {
 /// Unique identifier for a game
 pub type GameId = u64;
 
-/// Unique identifier for a player
+/// Unique identifier for a player: the chain owner that signs their operations
 pub type PlayerId = String;
 
 /// Represents a player in the game
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Player {
     X,
     O,
@@ -39,6 +63,29 @@ pub enum GameStatus {
     Finished,
 }
 
+/// Board dimensions and the number of marks in a row needed to win
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub width: usize,
+    pub height: usize,
+    /// Number of stacked layers, 1 for flat boards
+    pub depth: usize,
+    pub win_length: usize,
+}
+
+/// Largest supported board side, enough for Gomoku on a 19x19 Go board
+pub const MAX_BOARD_DIMENSION: usize = 19;
+
+/// How new games are played: variant, board, rule options and time control
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSettings {
+    pub variant: GameVariant,
+    pub config: BoardConfig,
+    pub options: RuleOptions,
+    /// Base time and increment of each player, `None` for untimed games
+    pub time_control: Option<TimeControl>,
+}
+
 /// Information about a player
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerInfo {
@@ -47,15 +94,87 @@ pub struct PlayerInfo {
     pub symbol: Player,
 }
 
+/// Why a game finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FinishReason {
+    /// A line was completed
+    LineCompleted,
+    /// The board filled up without a line
+    BoardFull,
+    /// Every winning line was blocked before the board filled up
+    NoLineLeft,
+    /// A player resigned
+    Resignation,
+    /// A player ran out of time
+    Timeout,
+    /// Both players agreed to a draw
+    AgreedDraw,
+    /// The challenged player turned the challenge down before it started
+    Declined,
+}
+
+/// How a finished game ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameOutcome {
+    pub reason: FinishReason,
+    /// Board indices of the completed line, empty when no line decided the game
+    pub winning_line: Vec<usize>,
+    /// Number of moves played when the game ended
+    pub move_number: usize,
+}
+
+/// A move that was played, in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveRecord {
+    pub player_id: PlayerId,
+    /// The move as submitted, with the placed symbol filled in
+    pub mv: Move,
+    /// Board index that was filled
+    pub position: usize,
+    pub timestamp: Timestamp,
+}
+
 /// Game state structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub id: GameId,
-    pub board: [Option<Player>; 9],
+    pub variant: GameVariant,
+    pub options: RuleOptions,
+    pub config: BoardConfig,
+    /// Who may find and join the game
+    pub visibility: Visibility,
+    pub board: Vec<Option<Player>>,
+    /// Meta board of an Ultimate game, `None` for other variants
+    pub ultimate: Option<UltimateBoard>,
     pub current_player: Player,
     pub status: GameStatus,
     pub players: Vec<PlayerInfo>,
+    /// Every move played so far, oldest first
+    pub moves: Vec<MoveRecord>,
+    /// Player asking to take back the last move, until the opponent answers
+    pub takeback_requested_by: Option<PlayerId>,
+    /// Player offering a draw, until the opponent answers or moves
+    pub draw_offered_by: Option<PlayerId>,
+    /// Player offering a rematch of this finished game, until the opponent accepts
+    pub rematch_offered_by: Option<PlayerId>,
+    /// Game this one is a rematch of
+    pub previous_game_id: Option<GameId>,
+    /// Rematch created from this game, once accepted
+    pub rematch_game_id: Option<GameId>,
+    /// Match this game belongs to, `None` for single games
+    pub match_id: Option<MatchId>,
+    /// Player this game was sent to as a challenge; only they may accept or decline it
+    pub challenged: Option<PlayerId>,
+    /// Symbol of the winning player
     pub winner: Option<Player>,
+    /// Identity of the winning player
+    pub winner_id: Option<PlayerId>,
+    /// How the game ended, once it is finished
+    pub outcome: Option<GameOutcome>,
+    /// Difficulty of the bot in the second seat, `None` for games between people
+    pub bot: Option<Difficulty>,
+    /// Clocks of both players, `None` for untimed games
+    pub clock: Option<GameClock>,
     pub created_at: Timestamp,
     pub finished_at: Option<Timestamp>,
 }
@@ -67,24 +186,198 @@ pub struct PlayerStats {
     pub wins: u32,
     pub losses: u32,
     pub draws: u32,
+    /// Games against the bot, kept apart from the ranked record above
+    pub bot_games_played: u32,
+    pub bot_wins: u32,
+    pub bot_losses: u32,
+    pub bot_draws: u32,
+    /// Best-of-N series; their games are kept out of the records above
+    pub matches_played: u32,
+    pub match_wins: u32,
+    pub match_losses: u32,
+    pub match_draws: u32,
 }
 
 /// Operations that can be performed on the contract
+///
+/// Every operation names the player it acts for in `player_id`, which must be
+/// the operation's authenticated signer or one of the player's session keys.
+/// `player_name` is only shown to others.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Operation {
     CreateGame {
         player_id: PlayerId,
         player_name: String,
+        width: usize,
+        height: usize,
+        win_length: usize,
+        variant: GameVariant,
+        options: RuleOptions,
+        /// Base time and increment of each player, `None` for an untimed game
+        time_control: Option<TimeControl>,
+        /// A join code hash must be salted with the nonce given next to it, see
+        /// `visibility::hash_join_code`
+        visibility: Visibility,
     },
     JoinGame {
         game_id: GameId,
         player_id: PlayerId,
         player_name: String,
+        /// Code that opens a private game to players who were not invited
+        join_code: Option<String>,
     },
     MakeMove {
         game_id: GameId,
         player_id: PlayerId,
+        /// Board index, or the column to drop into for Gravity games
         position: usize,
+        /// Local board to play in, required for Ultimate games
+        sub_board: Option<usize>,
+        /// Symbol to place, only allowed in wild games
+        symbol: Option<Player>,
+    },
+    /// Make a move by coordinates; `z` is the layer, always 0 on flat boards.
+    /// Ultimate games take coordinates on the whole 9x9 board, and Gravity
+    /// games drop into column `x` whatever the row
+    MakeMoveAt {
+        game_id: GameId,
+        player_id: PlayerId,
+        x: usize,
+        y: usize,
+        z: usize,
+        /// Symbol to place, only allowed in wild games
+        symbol: Option<Player>,
+    },
+    /// Ask the opponent to undo your last move; not available in timed games
+    RequestTakeback {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Agree to the opponent's takeback request, undoing the last move
+    AcceptTakeback {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Start a classic game against the bot, which replies to each move in the same block
+    CreateBotGame {
+        player_id: PlayerId,
+        player_name: String,
+        difficulty: Difficulty,
+    },
+    /// Win a timed game whose opponent has run out of time on their turn
+    ClaimTimeout {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Give up a game in progress, handing the win to the opponent
+    Resign {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Offer the opponent a draw; the offer lapses when the opponent moves
+    OfferDraw {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Accept or decline the opponent's draw offer
+    RespondToDraw {
+        game_id: GameId,
+        player_id: PlayerId,
+        accept: bool,
+    },
+    /// Offer the opponent a new game after this one finished
+    OfferRematch {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Accept the opponent's rematch offer, starting a new game with symbols swapped
+    AcceptRematch {
+        game_id: GameId,
+        player_id: PlayerId,
+    },
+    /// Open a best-of-N series; every game is played on the same board and rules
+    CreateMatch {
+        player_id: PlayerId,
+        player_name: String,
+        /// Odd number of games at most; winning a majority of them wins the match
+        best_of: u32,
+        width: usize,
+        height: usize,
+        win_length: usize,
+        variant: GameVariant,
+        options: RuleOptions,
+        time_control: Option<TimeControl>,
+    },
+    /// Join a match as the second player, starting its first game
+    JoinMatch {
+        match_id: MatchId,
+        player_id: PlayerId,
+        player_name: String,
+    },
+    /// Claim a unique handle and set up a profile
+    RegisterProfile {
+        player_id: PlayerId,
+        handle: String,
+        display_name: String,
+        avatar_hash: Option<CryptoHash>,
+    },
+    /// Change the handle of one's profile
+    RenameProfile {
+        player_id: PlayerId,
+        handle: String,
+    },
+    /// Change the display name and avatar of one's profile
+    UpdateProfile {
+        player_id: PlayerId,
+        display_name: String,
+        avatar_hash: Option<CryptoHash>,
+    },
+    /// Let `key` sign operations on games for the player until `expires_at`
+    AuthorizeSessionKey {
+        player_id: PlayerId,
+        key: PlayerId,
+        expires_at: Timestamp,
+        scope: SessionScope,
+    },
+    /// Withdraw a session key before it expires
+    RevokeSessionKey {
+        player_id: PlayerId,
+        key: PlayerId,
+    },
+    Follow {
+        player_id: PlayerId,
+        target: PlayerId,
+    },
+    Unfollow {
+        player_id: PlayerId,
+        target: PlayerId,
+    },
+    /// Block a player from challenging you or joining your games, ending follows both ways
+    Block {
+        player_id: PlayerId,
+        target: PlayerId,
+    },
+    Unblock {
+        player_id: PlayerId,
+        target: PlayerId,
+    },
+    /// Open a game that only `opponent` may join
+    Challenge {
+        player_id: PlayerId,
+        player_name: String,
+        opponent: PlayerId,
+        rules: ChallengeRules,
+    },
+    /// Take the seat of a challenge sent to you
+    AcceptChallenge {
+        game_id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+    },
+    /// Turn down a challenge sent to you
+    DeclineChallenge {
+        game_id: GameId,
+        player_id: PlayerId,
     },
 }
 
@@ -98,10 +391,15 @@ pub enum Message {
 }
 
 /// Queries that can be made to the contract
+///
+/// `viewer` is whoever the caller says they are. It only decides which games
+/// are shown, see the `visibility` module; it does not protect anything.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Query {
-    GetGame { game_id: GameId },
-    GetAllGames,
+    /// A game, unless it is private and `viewer` is not one of its players or invitees
+    GetGame { game_id: GameId, viewer: Option<PlayerId> },
+    /// Listed games, plus unlisted and private ones `viewer` plays in
+    GetAllGames { viewer: Option<PlayerId> },
     GetPlayerStats { player_id: PlayerId },
 }
 
@@ -130,34 +428,456 @@ pub enum TicTacToeError {
     PlayerNotInGame,
     #[error("Invalid position: {0}")]
     InvalidPosition(usize),
+    #[error("A sub-board must be given for this game")]
+    MissingSubBoard,
+    #[error("Invalid sub-board: {0}")]
+    InvalidSubBoard(usize),
+    #[error("Must play in board {0}")]
+    MustPlayInBoard(usize),
+    #[error("Board {0} is already finished")]
+    SubBoardFinished(usize),
+    #[error("There is no move to take back")]
+    NoMoveToTakeBack,
+    #[error("No takeback has been requested")]
+    NoTakebackRequested,
+    #[error("A takeback must be accepted by the opponent")]
+    CannotAcceptOwnTakeback,
+    #[error("Only the player who made the last move can ask to take it back")]
+    NotYourMove,
+    #[error("Column {0} is full")]
+    ColumnFull(usize),
+    #[error("Only wild games let you place your opponent's symbol")]
+    SymbolNotAllowed,
+    #[error("Invalid coordinates: ({x}, {y}, {z})")]
+    InvalidCoordinates { x: usize, y: usize, z: usize },
+    #[error("Invalid board: {width}x{height} with {win_length} in a row")]
+    InvalidBoardConfig {
+        width: usize,
+        height: usize,
+        win_length: usize,
+    },
+    #[error("Only classic 3x3 positions can be solved")]
+    NotSolvable,
+    #[error("Game is not finished yet")]
+    GameNotFinished,
+    #[error("A time control needs a positive base time")]
+    InvalidTimeControl,
+    #[error("Game is not timed")]
+    GameNotTimed,
+    #[error("Your time has run out")]
+    OutOfTime,
+    #[error("Your opponent still has time left")]
+    OpponentHasTime,
+    #[error("Moves cannot be taken back in timed games")]
+    TakebackInTimedGame,
+    #[error("No draw has been offered")]
+    NoDrawOffered,
+    #[error("A draw offer must be answered by the opponent")]
+    CannotAnswerOwnDrawOffer,
+    #[error("No rematch has been offered")]
+    NoRematchOffered,
+    #[error("A rematch must be accepted by the opponent")]
+    CannotAcceptOwnRematch,
+    #[error("A rematch of this game has already been played")]
+    RematchAlreadyCreated,
+    #[error("Only games that were played out between two players can be rematched")]
+    NothingToRematch,
+    #[error("Match not found")]
+    MatchNotFound,
+    #[error("A match must be best of an odd number of games, not {0}")]
+    InvalidBestOf(u32),
+    #[error("Games in a match are followed by the match's next game")]
+    GameInMatch,
+    #[error("You are already in this game")]
+    AlreadyInGame,
+    #[error("This game is private and you are not invited")]
+    NotInvited,
+    #[error("Invalid handle {0:?}: use 3 to 20 letters, digits, '_' or '-'")]
+    InvalidHandle(String),
+    #[error("Handle {0:?} is already taken")]
+    HandleTaken(String),
+    #[error("Display name must be 1 to 32 characters and not blank")]
+    InvalidDisplayName,
+    #[error("Profile not found")]
+    ProfileNotFound,
+    #[error("You already have a profile")]
+    ProfileExists,
+    #[error("Operation must be signed by the player it acts for")]
+    Unauthenticated,
+    #[error("Operation for player {claimed} was signed by {signer}")]
+    SignerMismatch { claimed: PlayerId, signer: PlayerId },
+    #[error("A session key must differ from the player and expire in the future")]
+    InvalidSessionKey,
+    #[error("Session key not found")]
+    SessionKeyNotFound,
+    #[error("Session key has expired")]
+    SessionKeyExpired,
+    #[error("Session keys may only act on games within their scope")]
+    SessionKeyOutOfScope,
+    #[error("You cannot follow, block or challenge yourself")]
+    CannotTargetYourself,
+    #[error("You have blocked this player")]
+    PlayerBlocked,
+    #[error("This player has blocked you")]
+    BlockedByPlayer,
+    #[error("This game is not a challenge to you")]
+    NotChallenged,
+    #[error("This challenge was already accepted or declined")]
+    ChallengeClosed,
+}
+
+impl Operation {
+    /// The player the operation acts for
+    pub fn player_id(&self) -> &PlayerId {
+        match self {
+            Operation::CreateGame { player_id, .. }
+            | Operation::JoinGame { player_id, .. }
+            | Operation::MakeMove { player_id, .. }
+            | Operation::MakeMoveAt { player_id, .. }
+            | Operation::RequestTakeback { player_id, .. }
+            | Operation::AcceptTakeback { player_id, .. }
+            | Operation::CreateBotGame { player_id, .. }
+            | Operation::ClaimTimeout { player_id, .. }
+            | Operation::Resign { player_id, .. }
+            | Operation::OfferDraw { player_id, .. }
+            | Operation::RespondToDraw { player_id, .. }
+            | Operation::OfferRematch { player_id, .. }
+            | Operation::AcceptRematch { player_id, .. }
+            | Operation::CreateMatch { player_id, .. }
+            | Operation::JoinMatch { player_id, .. }
+            | Operation::RegisterProfile { player_id, .. }
+            | Operation::RenameProfile { player_id, .. }
+            | Operation::UpdateProfile { player_id, .. }
+            | Operation::AuthorizeSessionKey { player_id, .. }
+            | Operation::RevokeSessionKey { player_id, .. }
+            | Operation::Follow { player_id, .. }
+            | Operation::Unfollow { player_id, .. }
+            | Operation::Block { player_id, .. }
+            | Operation::Unblock { player_id, .. }
+            | Operation::Challenge { player_id, .. }
+            | Operation::AcceptChallenge { player_id, .. }
+            | Operation::DeclineChallenge { player_id, .. } => player_id,
+        }
+    }
+
+    /// The existing game the operation acts on, if any
+    pub fn game_id(&self) -> Option<GameId> {
+        match self {
+            Operation::MakeMove { game_id, .. }
+            | Operation::MakeMoveAt { game_id, .. }
+            | Operation::RequestTakeback { game_id, .. }
+            | Operation::AcceptTakeback { game_id, .. }
+            | Operation::ClaimTimeout { game_id, .. }
+            | Operation::Resign { game_id, .. }
+            | Operation::OfferDraw { game_id, .. }
+            | Operation::RespondToDraw { game_id, .. }
+            | Operation::OfferRematch { game_id, .. }
+            | Operation::AcceptRematch { game_id, .. } => Some(*game_id),
+            // Joining takes a seat rather than playing one
+            Operation::JoinGame { .. }
+            | Operation::AcceptChallenge { .. }
+            | Operation::DeclineChallenge { .. }
+            | Operation::CreateGame { .. }
+            | Operation::CreateBotGame { .. }
+            | Operation::CreateMatch { .. }
+            | Operation::JoinMatch { .. }
+            | Operation::RegisterProfile { .. }
+            | Operation::RenameProfile { .. }
+            | Operation::UpdateProfile { .. }
+            | Operation::AuthorizeSessionKey { .. }
+            | Operation::RevokeSessionKey { .. }
+            | Operation::Follow { .. }
+            | Operation::Unfollow { .. }
+            | Operation::Block { .. }
+            | Operation::Unblock { .. }
+            | Operation::Challenge { .. } => None,
+        }
+    }
+
+    /// Check that the operation is signed by the player it acts for
+    pub fn authorize(&self, signer: Option<&PlayerId>) -> Result<(), TicTacToeError> {
+        let signer = signer.ok_or(TicTacToeError::Unauthenticated)?;
+        let claimed = self.player_id();
+        if claimed != signer {
+            return Err(TicTacToeError::SignerMismatch {
+                claimed: claimed.clone(),
+                signer: signer.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Player {
+    /// The other side
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+}
+
+impl BoardConfig {
+    /// Create a board configuration, rejecting sizes no game can be played on
+    pub fn new(width: usize, height: usize, win_length: usize) -> Result<Self, TicTacToeError> {
+        let valid_side = |side: usize| (1..=MAX_BOARD_DIMENSION).contains(&side);
+        if !valid_side(width)
+            || !valid_side(height)
+            || win_length < 2
+            || win_length > width.max(height)
+        {
+            return Err(TicTacToeError::InvalidBoardConfig {
+                width,
+                height,
+                win_length,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            depth: 1,
+            win_length,
+        })
+    }
+
+    /// Number of cells on the board
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+
+    /// Board index of the cell at column `x`, row `y` of layer `z`
+    pub fn position(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+        if x < self.width && y < self.height && z < self.depth {
+            Some((z * self.height + y) * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Every run of `win_length` cells that wins the game, as board indices
+    pub fn winning_lines(&self) -> Vec<Vec<usize>> {
+        let size = [self.width as isize, self.height as isize, self.depth as isize];
+        let k = self.win_length as isize;
+        // One of each pair of opposite (dx, dy, dz) steps: 13 in space, 4 of them in the plane
+        let directions: Vec<[isize; 3]> = (-1..=1)
+            .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| [dx, dy, dz])))
+            .filter(|step| step.iter().rev().find(|&&d| d != 0) == Some(&1))
+            .collect();
+        let mut lines = Vec::new();
+
+        for z in 0..size[2] {
+            for y in 0..size[1] {
+                for x in 0..size[0] {
+                    for step in &directions {
+                        let start = [x, y, z];
+                        let fits = (0..3).all(|axis| {
+                            let end = start[axis] + step[axis] * (k - 1);
+                            end >= 0 && end < size[axis]
+                        });
+                        if !fits {
+                            continue;
+                        }
+                        let line = (0..k)
+                            .map(|i| {
+                                let [cx, cy, cz] = [0, 1, 2].map(|axis| start[axis] + step[axis] * i);
+                                ((cz * size[1] + cy) * size[0] + cx) as usize
+                            })
+                            .collect();
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl GameSettings {
+    /// Check the requested board and time control; variants with a board of
+    /// their own ignore the requested size
+    pub fn new(
+        variant: GameVariant,
+        width: usize,
+        height: usize,
+        win_length: usize,
+        options: RuleOptions,
+        time_control: Option<TimeControl>,
+    ) -> Result<Self, TicTacToeError> {
+        let settings = Self {
+            variant,
+            config: variant.board_config(width, height, win_length)?,
+            options,
+            time_control,
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Reject time controls that would flag a player before the first move
+    pub fn validate(&self) -> Result<(), TicTacToeError> {
+        if let Some(control) = &self.time_control {
+            control.validate()?;
+        }
+        Ok(())
+    }
+
+    /// A game with these settings, waiting for an opponent
+    pub fn new_game(
+        &self,
+        id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        created_at: Timestamp,
+    ) -> Game {
+        let game =
+            Game::with_variant(id, player_id, player_name, self.variant, self.config, created_at)
+                .with_options(self.options);
+        match self.time_control {
+            Some(control) => game.with_time_control(control),
+            None => game,
+        }
+    }
+}
+
+impl Default for GameSettings {
+    /// Classic untimed 3x3 Tic Tac Toe
+    fn default() -> Self {
+        Self {
+            variant: GameVariant::Standard,
+            config: BoardConfig::default(),
+            options: RuleOptions::default(),
+            time_control: None,
+        }
+    }
+}
+
+impl Default for BoardConfig {
+    /// Classic 3x3 Tic Tac Toe
+    fn default() -> Self {
+        Self {
+            width: 3,
+            height: 3,
+            depth: 1,
+            win_length: 3,
+        }
+    }
 }
 
 impl Game {
-    /// Create a new game with the first player
+    /// Create a new classic 3x3 game with the first player
     pub fn new(id: GameId, player_id: PlayerId, player_name: String, created_at: Timestamp) -> Self {
+        Self::with_config(id, player_id, player_name, BoardConfig::default(), created_at)
+    }
+
+    /// Create a new standard game on a board of the given size with the first player
+    pub fn with_config(
+        id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        config: BoardConfig,
+        created_at: Timestamp,
+    ) -> Self {
+        Self::with_variant(id, player_id, player_name, GameVariant::Standard, config, created_at)
+    }
+
+    /// Create a new game played under the given variant with the first player
+    pub fn with_variant(
+        id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        variant: GameVariant,
+        config: BoardConfig,
+        created_at: Timestamp,
+    ) -> Self {
         let player_info = PlayerInfo {
             id: player_id,
             name: player_name,
             symbol: Player::X,
         };
+        let config = variant.rules().fixed_board().unwrap_or(config);
 
         Self {
             id,
-            board: [None; 9],
+            variant,
+            options: RuleOptions::default(),
+            config,
+            visibility: Visibility::Public,
+            board: vec![None; config.cell_count()],
+            ultimate: (variant == GameVariant::Ultimate).then(UltimateBoard::default),
             current_player: Player::X,
             status: GameStatus::WaitingForPlayer,
             players: vec![player_info],
+            moves: Vec::new(),
+            takeback_requested_by: None,
+            draw_offered_by: None,
+            rematch_offered_by: None,
+            previous_game_id: None,
+            rematch_game_id: None,
+            match_id: None,
+            challenged: None,
             winner: None,
+            winner_id: None,
+            outcome: None,
+            bot: None,
+            clock: None,
             created_at,
             finished_at: None,
         }
     }
 
+    /// Create a classic 3x3 game against the bot, ready to play
+    pub fn against_bot(
+        id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        difficulty: Difficulty,
+        created_at: Timestamp,
+    ) -> Self {
+        let mut game = Self::new(id, player_id, player_name, created_at);
+        game.players.push(PlayerInfo {
+            id: bot::BOT_PLAYER_ID.to_string(),
+            name: difficulty.bot_name(),
+            symbol: Player::O,
+        });
+        game.status = GameStatus::InProgress;
+        game.bot = Some(difficulty);
+        game
+    }
+
+    /// Play the game under the given rule options
+    pub fn with_options(mut self, options: RuleOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Restrict who may find and join the game
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Give both players a clock; it starts when the second player joins
+    pub fn with_time_control(mut self, control: TimeControl) -> Self {
+        self.clock = Some(GameClock::new(control));
+        self
+    }
+
+    /// The player whose turn it is
+    pub fn player_to_move(&self) -> Option<&PlayerInfo> {
+        self.players.iter().find(|p| p.symbol == self.current_player)
+    }
+
     /// Add a second player to the game
     pub fn add_player(&mut self, player_id: PlayerId, player_name: String) -> Result<(), TicTacToeError> {
         if self.players.len() >= 2 {
             return Err(TicTacToeError::GameFull);
         }
+        if self.players.iter().any(|p| p.id == player_id) {
+            return Err(TicTacToeError::AlreadyInGame);
+        }
 
         let player_info = PlayerInfo {
             id: player_id,
@@ -170,84 +890,388 @@ impl Game {
         Ok(())
     }
 
-    /// Make a move in the game
-    pub fn make_move(&mut self, player_id: &PlayerId, position: usize, timestamp: Timestamp) -> Result<(), TicTacToeError> {
-        // Validate position
-        if position >= 9 {
-            return Err(TicTacToeError::InvalidPosition(position));
+    /// Check that `player_id` may take the open seat, presenting `join_code`
+    pub fn check_admission(&self, player_id: &PlayerId, join_code: Option<&str>) -> Result<(), TicTacToeError> {
+        if self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::AlreadyInGame);
+        }
+        if !self.visibility.admits(player_id, join_code) {
+            return Err(TicTacToeError::NotInvited);
         }
+        Ok(())
+    }
+
+    /// Whether the game shows up in listings requested by `viewer`
+    pub fn is_visible_to(&self, viewer: Option<&PlayerId>) -> bool {
+        self.visibility.is_listed()
+            || viewer.is_some_and(|viewer| {
+                self.players.iter().any(|p| p.id == *viewer) || self.visibility.is_invited(viewer)
+            })
+    }
+
+    /// Whether `viewer` may look the game up by its id
+    pub fn is_reachable_by(&self, viewer: Option<&PlayerId>) -> bool {
+        self.visibility.is_open_by_id() || self.is_visible_to(viewer)
+    }
+
+    /// Add a second player and start the first player's clock
+    pub fn join(
+        &mut self,
+        player_id: PlayerId,
+        player_name: String,
+        timestamp: Timestamp,
+    ) -> Result<(), TicTacToeError> {
+        self.add_player(player_id, player_name)?;
+        if let Some(clock) = &mut self.clock {
+            clock.start(timestamp);
+        }
+        Ok(())
+    }
 
+    /// Make a move on a single-board game
+    pub fn make_move(&mut self, player_id: &PlayerId, position: usize, timestamp: Timestamp) -> Result<(), TicTacToeError> {
+        self.play(player_id, Move::at(position), timestamp)
+    }
+
+    /// The move playing the cell at column `x`, row `y` of layer `z`, as the variant reads it
+    pub fn move_at(&self, x: usize, y: usize, z: usize) -> Result<Move, TicTacToeError> {
+        self.variant.rules().move_at(self, x, y, z)
+    }
+
+    /// Make a move in the game
+    pub fn play(&mut self, player_id: &PlayerId, mv: Move, timestamp: Timestamp) -> Result<(), TicTacToeError> {
         // Check if game is in progress
         if self.status != GameStatus::InProgress {
             return Err(TicTacToeError::GameNotInProgress);
         }
 
-        // Check if position is empty
-        if self.board[position].is_some() {
-            return Err(TicTacToeError::InvalidMove(position));
+        // Find the player and check if it's their turn
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
         }
 
-        // Find the player and check if it's their turn
-        let player = self.players.iter()
+        let mover = self.player_to_move().ok_or(TicTacToeError::NotYourTurn)?;
+        if mover.id != *player_id {
+            return Err(TicTacToeError::NotYourTurn);
+        }
+
+        // A move cannot be made on a clock that has run out
+        if let Some(clock) = &self.clock {
+            if clock.is_flagged(mover.symbol, timestamp) {
+                return Err(TicTacToeError::OutOfTime);
+            }
+        }
+
+        // Only wild games let a player place the other symbol
+        if !self.options.wild && mv.mark(mover.symbol) != mover.symbol {
+            return Err(TicTacToeError::SymbolNotAllowed);
+        }
+
+        // Let the variant validate and place the move
+        let rules = self.variant.rules();
+        rules.validate_move(self, &mv)?;
+        let mover_symbol = mover.symbol;
+        let mv = mv.with_symbol(mv.mark(mover_symbol));
+        let position = rules.apply_move(self, &mv);
+        if let Some(clock) = &mut self.clock {
+            clock.punch(mover_symbol, timestamp);
+        }
+
+        // A new move makes any pending takeback request stale, and answers
+        // the opponent's draw offer by playing on
+        self.takeback_requested_by = None;
+        if self.draw_offered_by.as_ref().is_some_and(|offered_by| offered_by != player_id) {
+            self.draw_offered_by = None;
+        }
+        self.moves.push(MoveRecord {
+            player_id: player_id.clone(),
+            mv,
+            position,
+            timestamp,
+        });
+
+        match rules.outcome(self) {
+            Some(Outcome::Win { line, .. }) => {
+                // Whoever completes a line wins it, whichever symbol it is made of,
+                // and loses it under misère rules
+                let completed_by = self.current_player;
+                let winner = if self.options.misere {
+                    completed_by.opponent()
+                } else {
+                    completed_by
+                };
+                self.finish(Some(winner), FinishReason::LineCompleted, line, timestamp);
+            }
+            Some(Outcome::Draw) => {
+                self.finish(None, FinishReason::BoardFull, Vec::new(), timestamp);
+            }
+            None if self.options.early_draw && !rules.has_open_line(self) => {
+                self.finish(None, FinishReason::NoLineLeft, Vec::new(), timestamp);
+            }
+            None => {
+                self.current_player = rules.next_player(self);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Win the game because the opponent's clock ran out on their turn
+    pub fn claim_timeout(&mut self, player_id: &PlayerId, timestamp: Timestamp) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        let claimant = self
+            .players
+            .iter()
             .find(|p| p.id == *player_id)
-            .ok_or(TicTacToeError::PlayerNotInGame)?;
+            .ok_or(TicTacToeError::PlayerNotInGame)?
+            .symbol;
+        let clock = self.clock.as_ref().ok_or(TicTacToeError::GameNotTimed)?;
+        if claimant == self.current_player || !clock.is_flagged(self.current_player, timestamp) {
+            return Err(TicTacToeError::OpponentHasTime);
+        }
 
-        if player.symbol != self.current_player {
-            return Err(TicTacToeError::NotYourTurn);
+        self.finish(Some(claimant), FinishReason::Timeout, Vec::new(), timestamp);
+        Ok(())
+    }
+
+    /// Give up the game, handing the win to the opponent
+    pub fn resign(&mut self, player_id: &PlayerId, timestamp: Timestamp) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
         }
+        let resigning = self
+            .players
+            .iter()
+            .find(|p| p.id == *player_id)
+            .ok_or(TicTacToeError::PlayerNotInGame)?
+            .symbol;
 
-        // Make the move
-        self.board[position] = Some(self.current_player);
+        self.finish(Some(resigning.opponent()), FinishReason::Resignation, Vec::new(), timestamp);
+        Ok(())
+    }
 
-        // Check for winner
-        if let Some(winner) = self.check_winner() {
-            self.winner = Some(winner);
-            self.status = GameStatus::Finished;
-            self.finished_at = Some(timestamp);
-        } else if self.is_board_full() {
-            // It's a draw
-            self.status = GameStatus::Finished;
-            self.finished_at = Some(timestamp);
-        } else {
-            // Switch turns
-            self.current_player = match self.current_player {
-                Player::X => Player::O,
-                Player::O => Player::X,
-            };
+    /// Offer the opponent a draw
+    pub fn offer_draw(&mut self, player_id: &PlayerId) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
         }
 
+        self.draw_offered_by = Some(player_id.clone());
         Ok(())
     }
 
-    /// Check if there's a winner
-    fn check_winner(&self) -> Option<Player> {
-        let winning_positions = [
-            // Rows
-            [0, 1, 2], [3, 4, 5], [6, 7, 8],
-            // Columns
-            [0, 3, 6], [1, 4, 7], [2, 5, 8],
-            // Diagonals
-            [0, 4, 8], [2, 4, 6],
-        ];
-
-        for positions in &winning_positions {
-            if let (Some(a), Some(b), Some(c)) = (
-                self.board[positions[0]],
-                self.board[positions[1]],
-                self.board[positions[2]],
-            ) {
-                if a == b && b == c {
-                    return Some(a);
-                }
-            }
+    /// Answer the opponent's draw offer, ending the game as a draw if accepted
+    pub fn respond_to_draw(
+        &mut self,
+        player_id: &PlayerId,
+        accept: bool,
+        timestamp: Timestamp,
+    ) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
+        }
+
+        let offered_by = self
+            .draw_offered_by
+            .as_ref()
+            .ok_or(TicTacToeError::NoDrawOffered)?;
+        if offered_by == player_id {
+            return Err(TicTacToeError::CannotAnswerOwnDrawOffer);
         }
 
-        None
+        self.draw_offered_by = None;
+        if accept {
+            self.finish(None, FinishReason::AgreedDraw, Vec::new(), timestamp);
+        }
+        Ok(())
     }
 
-    /// Check if the board is full
-    fn is_board_full(&self) -> bool {
-        self.board.iter().all(|cell| cell.is_some())
+    /// Turn down a challenge, ending the game before it starts
+    pub fn decline_challenge(&mut self, player_id: &PlayerId, timestamp: Timestamp) -> Result<(), TicTacToeError> {
+        if self.challenged.as_ref() != Some(player_id) {
+            return Err(TicTacToeError::NotChallenged);
+        }
+        if self.status != GameStatus::WaitingForPlayer {
+            return Err(TicTacToeError::ChallengeClosed);
+        }
+
+        self.finish(None, FinishReason::Declined, Vec::new(), timestamp);
+        Ok(())
+    }
+
+    /// Offer the opponent a rematch of this finished game
+    pub fn offer_rematch(&mut self, player_id: &PlayerId) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::Finished {
+            return Err(TicTacToeError::GameNotFinished);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
+        }
+        if self.rematch_game_id.is_some() {
+            return Err(TicTacToeError::RematchAlreadyCreated);
+        }
+        if self.match_id.is_some() {
+            return Err(TicTacToeError::GameInMatch);
+        }
+        // A declined challenge finishes before anyone took the second seat
+        let declined = self.outcome.as_ref().is_some_and(|o| o.reason == FinishReason::Declined);
+        if self.players.len() < 2 || declined {
+            return Err(TicTacToeError::NothingToRematch);
+        }
+
+        self.rematch_offered_by = Some(player_id.clone());
+        Ok(())
+    }
+
+    /// Accept the opponent's rematch offer, returning the new game `id`:
+    /// same board and rules, with X and O swapped so the other player starts
+    pub fn accept_rematch(
+        &mut self,
+        player_id: &PlayerId,
+        id: GameId,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        if self.status != GameStatus::Finished {
+            return Err(TicTacToeError::GameNotFinished);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
+        }
+        if self.rematch_game_id.is_some() {
+            return Err(TicTacToeError::RematchAlreadyCreated);
+        }
+
+        let offered_by = self
+            .rematch_offered_by
+            .as_ref()
+            .ok_or(TicTacToeError::NoRematchOffered)?;
+        if offered_by == player_id {
+            return Err(TicTacToeError::CannotAcceptOwnRematch);
+        }
+
+        // The first seat always plays X, so the previous O player takes it
+        let (first, second) = match self.players.as_slice() {
+            [x, o] => (o, x),
+            _ => return Err(TicTacToeError::PlayerNotInGame),
+        };
+        let mut rematch = Game::with_variant(
+            id,
+            first.id.clone(),
+            first.name.clone(),
+            self.variant,
+            self.config,
+            timestamp,
+        )
+        .with_options(self.options)
+        .with_visibility(self.visibility.clone());
+        if let Some(clock) = &self.clock {
+            rematch = rematch.with_time_control(clock.control);
+        }
+        rematch.join(second.id.clone(), second.name.clone(), timestamp)?;
+        rematch.bot = self.bot;
+        rematch.previous_game_id = Some(self.id);
+
+        self.rematch_offered_by = None;
+        self.rematch_game_id = Some(id);
+        Ok(rematch)
+    }
+
+    /// End the game, crediting `winner` or recording a draw
+    fn finish(
+        &mut self,
+        winner: Option<Player>,
+        reason: FinishReason,
+        winning_line: Vec<usize>,
+        timestamp: Timestamp,
+    ) {
+        self.winner = winner;
+        self.winner_id = winner.and_then(|symbol| {
+            self.players
+                .iter()
+                .find(|p| p.symbol == symbol)
+                .map(|p| p.id.clone())
+        });
+        self.outcome = Some(GameOutcome {
+            reason,
+            winning_line,
+            move_number: self.moves.len(),
+        });
+        self.status = GameStatus::Finished;
+        self.finished_at = Some(timestamp);
+        let to_move = self.current_player;
+        if let Some(clock) = &mut self.clock {
+            clock.stop(to_move, timestamp);
+        }
+    }
+
+    /// Ask the opponent to undo your last move
+    pub fn request_takeback(&mut self, player_id: &PlayerId) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
+        }
+        let last_move = self.moves.last().ok_or(TicTacToeError::NoMoveToTakeBack)?;
+        if last_move.player_id != *player_id {
+            return Err(TicTacToeError::NotYourMove);
+        }
+        // Undoing a move would leave its time and increment on the wrong clock
+        if self.clock.is_some() {
+            return Err(TicTacToeError::TakebackInTimedGame);
+        }
+
+        self.takeback_requested_by = Some(player_id.clone());
+        Ok(())
+    }
+
+    /// Accept the opponent's takeback request and undo the last move
+    pub fn accept_takeback(&mut self, player_id: &PlayerId) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        if !self.players.iter().any(|p| p.id == *player_id) {
+            return Err(TicTacToeError::PlayerNotInGame);
+        }
+
+        let requested_by = self
+            .takeback_requested_by
+            .as_ref()
+            .ok_or(TicTacToeError::NoTakebackRequested)?;
+        if requested_by == player_id {
+            return Err(TicTacToeError::CannotAcceptOwnTakeback);
+        }
+
+        self.moves.pop().ok_or(TicTacToeError::NoMoveToTakeBack)?;
+        self.takeback_requested_by = None;
+        self.replay_moves();
+        Ok(())
+    }
+
+    /// Rebuild the board and turn from the move history, so that variant
+    /// state such as Ultimate's meta board is restored along with the cells
+    fn replay_moves(&mut self) {
+        let rules = self.variant.rules();
+        self.board = vec![None; self.config.cell_count()];
+        if self.ultimate.is_some() {
+            self.ultimate = Some(UltimateBoard::default());
+        }
+        self.current_player = Player::X;
+
+        let moves = std::mem::take(&mut self.moves);
+        for record in &moves {
+            rules.apply_move(self, &record.mv);
+            self.current_player = rules.next_player(self);
+        }
+        self.moves = moves;
     }
 }
 
@@ -261,6 +1285,26 @@ impl PlayerStats {
             GameResult::Draw => self.draws += 1,
         }
     }
+
+    /// Update stats after a best-of-N series
+    pub fn update_after_match(&mut self, result: GameResult) {
+        self.matches_played += 1;
+        match result {
+            GameResult::Win => self.match_wins += 1,
+            GameResult::Loss => self.match_losses += 1,
+            GameResult::Draw => self.match_draws += 1,
+        }
+    }
+
+    /// Update stats after a game against the bot
+    pub fn update_after_bot_game(&mut self, result: GameResult) {
+        self.bot_games_played += 1;
+        match result {
+            GameResult::Win => self.bot_wins += 1,
+            GameResult::Loss => self.bot_losses += 1,
+            GameResult::Draw => self.bot_draws += 1,
+        }
+    }
 }
 
 /// Result of a game for a specific player
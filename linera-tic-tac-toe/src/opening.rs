@@ -0,0 +1,276 @@
+//! Opening explorer: how finished games went from each position.
+//!
+//! Positions are keyed up to the eight rotations and reflections of the
+//! square, so a corner opening counts the same whichever corner was taken.
+//! Tallies belong to positions rather than to the moves leading to them, so a
+//! continuation also counts games that reached the same position by another
+//! move order.
+
+use crate::{BoardConfig, Game, GameVariant, Player, RuleOptions, TicTacToeError};
+use serde::{Deserialize, Serialize};
+
+/// Symmetry-canonical key of a board position
+pub type PositionKey = String;
+
+/// Results of finished games that passed through a position
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeTally {
+    pub x_wins: u32,
+    pub o_wins: u32,
+    pub draws: u32,
+}
+
+impl OutcomeTally {
+    /// Count one more game won by `winner`, or drawn
+    pub fn record(&mut self, winner: Option<Player>) {
+        match winner {
+            Some(Player::X) => self.x_wins += 1,
+            Some(Player::O) => self.o_wins += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.x_wins + self.o_wins + self.draws
+    }
+}
+
+/// How games continued after one move from the explored position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Continuation {
+    /// One of the moves leading to the position, standing for all the moves
+    /// that lead to it up to symmetry
+    pub position: usize,
+    /// Counted from the side playing `position`
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Continuation {
+    /// Read a tally from the point of view of `mover`
+    pub fn from_tally(position: usize, mover: Player, tally: &OutcomeTally) -> Self {
+        let (wins, losses) = match mover {
+            Player::X => (tally.x_wins, tally.o_wins),
+            Player::O => (tally.o_wins, tally.x_wins),
+        };
+        Self {
+            position,
+            wins,
+            draws: tally.draws,
+            losses,
+        }
+    }
+}
+
+/// The eight symmetries of an `n` by `n` board, as maps from new to old cell index
+pub fn square_symmetries(n: usize) -> Vec<Vec<usize>> {
+    let last = n - 1;
+    let transform = |symmetry: usize, x: usize, y: usize| match symmetry {
+        0 => (x, y),
+        1 => (y, last - x),
+        2 => (last - x, last - y),
+        3 => (last - y, x),
+        4 => (last - x, y),
+        5 => (x, last - y),
+        6 => (y, x),
+        _ => (last - y, last - x),
+    };
+    (0..8)
+        .map(|symmetry| {
+            (0..n * n)
+                .map(|cell| {
+                    let (x, y) = transform(symmetry, cell % n, cell / n);
+                    y * n + x
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Key of a board, the smallest encoding over the square's symmetries
+pub fn canonical_key(config: &BoardConfig, board: &[Option<Player>]) -> PositionKey {
+    let encode = |cell: &Option<Player>| match cell {
+        None => '.',
+        Some(Player::X) => 'X',
+        Some(Player::O) => 'O',
+    };
+
+    if config.width != config.height || config.depth != 1 {
+        return board.iter().map(encode).collect();
+    }
+    square_symmetries(config.width)
+        .iter()
+        .map(|symmetry| symmetry.iter().map(|&from| encode(&board[from])).collect())
+        .min()
+        .expect("a square always has the identity symmetry")
+}
+
+/// Whether a game is classic 3x3 play, the only kind the explorer tallies
+pub fn is_classic(game: &Game) -> bool {
+    game.variant == GameVariant::Standard
+        && game.config == BoardConfig::default()
+        && game.options == RuleOptions::default()
+}
+
+/// Keys of every position a game passed through, starting from the empty board
+pub fn game_positions(game: &Game) -> Vec<PositionKey> {
+    let mut board = vec![None; game.board.len()];
+    let mut keys = vec![canonical_key(&game.config, &board)];
+    for record in &game.moves {
+        board[record.position] = record.mv.symbol;
+        keys.push(canonical_key(&game.config, &board));
+    }
+    keys
+}
+
+/// Each legal continuation of a classic opening, with the key it leads to,
+/// and the symbol that would play it. Moves leading to the same key up to
+/// symmetry are listed once, under the lowest position, so each game is
+/// counted under one continuation only.
+pub fn continuations(
+    moves: &[usize],
+) -> Result<(Player, Vec<(usize, PositionKey)>), TicTacToeError> {
+    let config = BoardConfig::default();
+    let mut board = vec![None; config.cell_count()];
+    let mut to_move = Player::X;
+    for &position in moves {
+        match board.get(position) {
+            None => return Err(TicTacToeError::InvalidPosition(position)),
+            Some(Some(_)) => return Err(TicTacToeError::InvalidMove(position)),
+            Some(None) => board[position] = Some(to_move),
+        }
+        to_move = to_move.opponent();
+    }
+
+    let mut children: Vec<(usize, PositionKey)> = Vec::new();
+    for position in (0..board.len()).filter(|&position| board[position].is_none()) {
+        let mut child = board.clone();
+        child[position] = Some(to_move);
+        let key = canonical_key(&config, &child);
+        if !children.iter().any(|(_, seen)| *seen == key) {
+            children.push((position, key));
+        }
+    }
+    Ok((to_move, children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::SYMMETRIES;
+    use linera_base::data_types::Timestamp;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_symmetries_match_the_solver() {
+        let symmetries = square_symmetries(3);
+
+        for symmetry in SYMMETRIES {
+            assert!(symmetries.contains(&symmetry.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_corners_share_a_key() {
+        let config = BoardConfig::default();
+        let keys: Vec<PositionKey> = [0, 2, 6, 8]
+            .iter()
+            .map(|&corner| {
+                let mut board = vec![None; 9];
+                board[corner] = Some(Player::X);
+                canonical_key(&config, &board)
+            })
+            .collect();
+
+        assert!(keys.iter().all(|key| *key == keys[0]));
+
+        let mut edge = vec![None; 9];
+        edge[1] = Some(Player::X);
+        assert_ne!(canonical_key(&config, &edge), keys[0]);
+    }
+
+    #[test]
+    fn test_larger_square_boards_are_canonical() {
+        let config = BoardConfig::new(4, 4, 3).unwrap();
+        let mut top_left = vec![None; 16];
+        top_left[1] = Some(Player::O);
+        let mut right_side = vec![None; 16];
+        right_side[7] = Some(Player::O);
+
+        assert_eq!(canonical_key(&config, &top_left), canonical_key(&config, &right_side));
+    }
+
+    #[test]
+    fn test_game_positions_follow_the_moves() {
+        let mut game = Game::new(1, "alice".to_string(), "Alice".to_string(), Timestamp::from(0));
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        game.make_move(&"alice".to_string(), 8, Timestamp::from(1)).unwrap();
+        game.make_move(&"bob".to_string(), 4, Timestamp::from(2)).unwrap();
+
+        let keys = game_positions(&game);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0], ".........");
+
+        let (to_move, children) = continuations(&[0]).unwrap();
+        assert_eq!(to_move, Player::O);
+        let centre = children.iter().find(|(position, _)| *position == 4).unwrap();
+        assert_eq!(centre.1, keys[2]);
+    }
+
+    #[test]
+    fn test_symmetric_continuations_are_listed_once() {
+        let (_, opening) = continuations(&[]).unwrap();
+        let positions: Vec<usize> = opening.iter().map(|(position, _)| *position).collect();
+        assert_eq!(positions, vec![0, 1, 4]);
+
+        let (_, after_centre) = continuations(&[4]).unwrap();
+        assert_eq!(after_centre.len(), 2);
+    }
+
+    #[test]
+    fn test_child_counts_sum_to_the_parent_count() {
+        let openings: [&[usize]; 6] = [&[0, 4], &[8, 4], &[2, 1], &[1, 0], &[4, 0], &[4, 7]];
+        let mut counts: HashMap<PositionKey, u32> = HashMap::new();
+        for opening in openings {
+            let mut game =
+                Game::new(1, "alice".to_string(), "Alice".to_string(), Timestamp::from(0));
+            game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+            for (turn, &position) in opening.iter().enumerate() {
+                let player = if turn.is_multiple_of(2) { "alice" } else { "bob" };
+                game.make_move(&player.to_string(), position, Timestamp::from(turn as u64 + 1))
+                    .unwrap();
+            }
+            for key in game_positions(&game) {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+
+        let child_total = |moves: &[usize]| -> u32 {
+            let (_, children) = continuations(moves).unwrap();
+            children.iter().map(|(_, key)| counts.get(key).copied().unwrap_or(0)).sum()
+        };
+        assert_eq!(child_total(&[]), 6);
+        assert_eq!(child_total(&[0]), 3);
+        assert_eq!(child_total(&[4]), 2);
+    }
+
+    #[test]
+    fn test_continuations_reject_illegal_openings() {
+        assert!(matches!(continuations(&[4, 4]), Err(TicTacToeError::InvalidMove(4))));
+        assert!(matches!(continuations(&[9]), Err(TicTacToeError::InvalidPosition(9))));
+    }
+
+    #[test]
+    fn test_tally_reads_from_the_movers_side() {
+        let mut tally = OutcomeTally::default();
+        tally.record(Some(Player::X));
+        tally.record(Some(Player::X));
+        tally.record(None);
+        tally.record(Some(Player::O));
+
+        assert_eq!(tally.games(), 4);
+        let for_o = Continuation::from_tally(4, Player::O, &tally);
+        assert_eq!((for_o.wins, for_o.draws, for_o.losses), (1, 1, 2));
+    }
+}
@@ -0,0 +1,109 @@
+//! Player profiles: a unique handle and a display name per player.
+//!
+//! Handles are unique regardless of case, so the registry indexes them by
+//! their lowercase form while each profile keeps the spelling its owner chose.
+//! Games refer to players by id and take display names from their profiles
+//! when read, so a rename shows up in every game at once.
+
+use crate::{PlayerId, TicTacToeError};
+use linera_base::{crypto::CryptoHash, data_types::Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// Shortest allowed handle
+pub const MIN_HANDLE_LENGTH: usize = 3;
+
+/// Longest allowed handle
+pub const MAX_HANDLE_LENGTH: usize = 20;
+
+/// Longest allowed display name
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 32;
+
+/// A registered player
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub player_id: PlayerId,
+    /// Unique handle, spelled as registered
+    pub handle: String,
+    pub display_name: String,
+    /// Hash of the avatar image, which is stored off chain
+    pub avatar_hash: Option<CryptoHash>,
+    pub created_at: Timestamp,
+}
+
+impl Profile {
+    pub fn new(
+        player_id: PlayerId,
+        handle: String,
+        display_name: String,
+        avatar_hash: Option<CryptoHash>,
+        created_at: Timestamp,
+    ) -> Result<Self, TicTacToeError> {
+        handle_key(&handle)?;
+        validate_display_name(&display_name)?;
+        Ok(Self {
+            player_id,
+            handle,
+            display_name,
+            avatar_hash,
+            created_at,
+        })
+    }
+}
+
+/// Index key of a handle: its lowercase form, after checking that it is
+/// made of letters, digits, `_` and `-` and has an allowed length
+pub fn handle_key(handle: &str) -> Result<String, TicTacToeError> {
+    let length = handle.chars().count();
+    let allowed = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if !(MIN_HANDLE_LENGTH..=MAX_HANDLE_LENGTH).contains(&length) || !handle.chars().all(allowed) {
+        return Err(TicTacToeError::InvalidHandle(handle.to_string()));
+    }
+    Ok(handle.to_ascii_lowercase())
+}
+
+/// Check that a display name is neither blank nor too long
+pub fn validate_display_name(display_name: &str) -> Result<(), TicTacToeError> {
+    if display_name.trim().is_empty() || display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+        return Err(TicTacToeError::InvalidDisplayName);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_keys_ignore_case() {
+        assert_eq!(handle_key("Alice_99").unwrap(), "alice_99");
+        assert_eq!(handle_key("ALICE_99").unwrap(), handle_key("alice_99").unwrap());
+    }
+
+    #[test]
+    fn test_malformed_handles_are_rejected() {
+        for handle in ["al", "alice smith", "ålice", "a".repeat(21).as_str(), ""] {
+            assert!(
+                matches!(handle_key(handle), Err(TicTacToeError::InvalidHandle(_))),
+                "{handle:?} should be rejected"
+            );
+        }
+        assert!(handle_key(&"a".repeat(20)).is_ok());
+    }
+
+    #[test]
+    fn test_profiles_need_a_display_name() {
+        let profile = |name: &str| {
+            Profile::new(
+                "owner".to_string(),
+                "alice".to_string(),
+                name.to_string(),
+                None,
+                Timestamp::from(0),
+            )
+        };
+
+        assert!(profile("Alice").is_ok());
+        assert!(matches!(profile("   "), Err(TicTacToeError::InvalidDisplayName)));
+        assert!(matches!(profile(&"x".repeat(33)), Err(TicTacToeError::InvalidDisplayName)));
+    }
+}
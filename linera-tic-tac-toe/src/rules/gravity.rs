@@ -0,0 +1,150 @@
+use super::{GameRules, Move, Outcome, StandardRules};
+use crate::{BoardConfig, Game, TicTacToeError};
+
+/// Connect Four: moves name a column and the piece falls to its lowest empty cell.
+///
+/// Row 0 is the top of the board, so pieces stack up from row `height - 1`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GravityRules;
+
+impl GravityRules {
+    /// Board index a piece dropped into `column` would land on, if the column has room
+    pub fn landing_cell(game: &Game, column: usize) -> Option<usize> {
+        let width = game.config.width;
+        (0..game.config.height)
+            .rev()
+            .map(|row| row * width + column)
+            .find(|&index| game.board[index].is_none())
+    }
+}
+
+impl GameRules for GravityRules {
+    fn fixed_board(&self) -> Option<BoardConfig> {
+        Some(BoardConfig {
+            width: 7,
+            height: 6,
+            depth: 1,
+            win_length: 4,
+        })
+    }
+
+    fn validate_move(&self, game: &Game, mv: &Move) -> Result<(), TicTacToeError> {
+        if let Some(sub_board) = mv.sub_board {
+            return Err(TicTacToeError::InvalidSubBoard(sub_board));
+        }
+        if mv.position >= game.config.width {
+            return Err(TicTacToeError::InvalidPosition(mv.position));
+        }
+        if Self::landing_cell(game, mv.position).is_none() {
+            return Err(TicTacToeError::ColumnFull(mv.position));
+        }
+
+        Ok(())
+    }
+
+    fn apply_move(&self, game: &mut Game, mv: &Move) -> usize {
+        let index = Self::landing_cell(game, mv.position).expect("validated column has room");
+        game.board[index] = Some(mv.mark(game.current_player));
+        index
+    }
+
+    fn move_at(&self, game: &Game, x: usize, y: usize, z: usize) -> Result<Move, TicTacToeError> {
+        // Any cell of a column drops a piece into that column
+        game.config
+            .position(x, y, z)
+            .map(|_| Move::at(x))
+            .ok_or(TicTacToeError::InvalidCoordinates { x, y, z })
+    }
+
+    fn target_cell(&self, game: &Game, mv: &Move) -> usize {
+        Self::landing_cell(game, mv.position).expect("legal column has room")
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        StandardRules.outcome(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameStatus, GameVariant, Player};
+    use linera_base::data_types::Timestamp;
+
+    fn gravity_game() -> Game {
+        let mut game = Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            GameVariant::Gravity,
+            BoardConfig::default(),
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        game
+    }
+
+    fn drop_in(game: &mut Game, column: usize) -> Result<(), TicTacToeError> {
+        let player_id = game.player_to_move().unwrap().id.clone();
+        game.make_move(&player_id, column, Timestamp::from(1))
+    }
+
+    #[test]
+    fn test_gravity_uses_seven_by_six_board() {
+        let game = gravity_game();
+
+        assert_eq!(game.config.width, 7);
+        assert_eq!(game.config.height, 6);
+        assert_eq!(game.config.win_length, 4);
+        assert_eq!(game.board.len(), 42);
+    }
+
+    #[test]
+    fn test_pieces_stack_from_the_bottom() {
+        let mut game = gravity_game();
+
+        drop_in(&mut game, 3).unwrap();
+        drop_in(&mut game, 3).unwrap();
+
+        assert_eq!(game.board[5 * 7 + 3], Some(Player::X));
+        assert_eq!(game.board[4 * 7 + 3], Some(Player::O));
+        assert_eq!(game.board[3 * 7 + 3], None);
+    }
+
+    #[test]
+    fn test_full_column_is_rejected() {
+        let mut game = gravity_game();
+        for _ in 0..6 {
+            drop_in(&mut game, 0).unwrap();
+        }
+
+        assert!(matches!(drop_in(&mut game, 0), Err(TicTacToeError::ColumnFull(0))));
+        assert!(matches!(drop_in(&mut game, 7), Err(TicTacToeError::InvalidPosition(7))));
+    }
+
+    #[test]
+    fn test_four_stacked_wins() {
+        let mut game = gravity_game();
+        for _ in 0..3 {
+            drop_in(&mut game, 2).unwrap(); // X
+            drop_in(&mut game, 5).unwrap(); // O
+        }
+        drop_in(&mut game, 2).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::X));
+    }
+
+    #[test]
+    fn test_bottom_row_win() {
+        let mut game = gravity_game();
+        for column in 0..3 {
+            drop_in(&mut game, column).unwrap(); // X
+            drop_in(&mut game, column).unwrap(); // O on top
+        }
+        drop_in(&mut game, 3).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::X));
+    }
+}
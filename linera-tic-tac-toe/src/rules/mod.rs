@@ -0,0 +1,177 @@
+//! Rule sets for the game variants supported by the contract.
+//!
+//! `Game::make_move` handles turn order and bookkeeping, then hands the
+//! variant-specific parts of a move to the `GameRules` implementation
+//! selected by the game's `GameVariant`.
+
+mod gravity;
+mod qubic;
+mod standard;
+mod ultimate;
+
+pub use gravity::GravityRules;
+pub use qubic::QubicRules;
+pub use standard::StandardRules;
+pub use ultimate::{UltimateBoard, UltimateRules};
+
+use crate::bitboard::{BitPosition, LineMasks};
+use crate::{BoardConfig, Game, Player, TicTacToeError};
+use serde::{Deserialize, Serialize};
+
+/// The rule set a game is played under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameVariant {
+    /// Get `win_length` in a row on a `width` x `height` board
+    #[default]
+    Standard,
+    /// A 3x3 grid of 3x3 boards; winning local boards wins cells on the meta board
+    Ultimate,
+    /// Four in a row in a 4x4x4 cube
+    Qubic,
+    /// Connect Four: moves name a column on a 7x6 board, four in a row wins
+    Gravity,
+}
+
+impl GameVariant {
+    /// The rules implementation for this variant
+    pub fn rules(&self) -> Box<dyn GameRules> {
+        match self {
+            GameVariant::Standard => Box::new(StandardRules),
+            GameVariant::Ultimate => Box::new(UltimateRules),
+            GameVariant::Qubic => Box::new(QubicRules),
+            GameVariant::Gravity => Box::new(GravityRules),
+        }
+    }
+
+    /// The board for a new game, using the requested size unless the variant fixes its own
+    pub fn board_config(
+        &self,
+        width: usize,
+        height: usize,
+        win_length: usize,
+    ) -> Result<BoardConfig, TicTacToeError> {
+        match self.rules().fixed_board() {
+            Some(config) => Ok(config),
+            None => BoardConfig::new(width, height, win_length),
+        }
+    }
+}
+
+/// Optional rules that apply on top of any variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RuleOptions {
+    /// Completing a line loses instead of winning
+    pub misere: bool,
+    /// On each turn the player chooses whether to place X or O
+    pub wild: bool,
+    /// End the game as a draw as soon as no line can be completed any more
+    pub early_draw: bool,
+}
+
+/// A move as submitted by a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    /// Cell on the board, the cell within `sub_board` for Ultimate games,
+    /// or the column for Gravity games
+    pub position: usize,
+    /// Local board the move is played in, for Ultimate games
+    pub sub_board: Option<usize>,
+    /// Symbol to place in wild games; defaults to the mover's own symbol
+    pub symbol: Option<Player>,
+}
+
+impl Move {
+    /// A move on a single-board variant
+    pub fn at(position: usize) -> Self {
+        Self {
+            position,
+            sub_board: None,
+            symbol: None,
+        }
+    }
+
+    /// A move on cell `position` of local board `sub_board`
+    pub fn in_sub_board(sub_board: usize, position: usize) -> Self {
+        Self {
+            sub_board: Some(sub_board),
+            ..Self::at(position)
+        }
+    }
+
+    /// The same move placing `symbol` instead of the mover's own
+    pub fn with_symbol(self, symbol: Player) -> Self {
+        Self {
+            symbol: Some(symbol),
+            ..self
+        }
+    }
+
+    /// The symbol this move places when made by `mover`
+    pub fn mark(&self, mover: Player) -> Player {
+        self.symbol.unwrap_or(mover)
+    }
+}
+
+/// How a position ended, if it has
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// A line of `symbol` was completed on the given board indices
+    Win { symbol: Player, line: Vec<usize> },
+    Draw,
+}
+
+/// Variant-specific game rules
+pub trait GameRules {
+    /// The board this variant is always played on, if it ignores the requested size
+    fn fixed_board(&self) -> Option<BoardConfig> {
+        None
+    }
+
+    /// Check that `mv` is a legal move for the player to move
+    fn validate_move(&self, game: &Game, mv: &Move) -> Result<(), TicTacToeError>;
+
+    /// Place the current player's mark for an already validated move,
+    /// returning the board index that was filled
+    fn apply_move(&self, game: &mut Game, mv: &Move) -> usize;
+
+    /// Decide whether the game is over after the last move
+    fn outcome(&self, game: &Game) -> Option<Outcome>;
+
+    /// Choose who moves after the current player
+    fn next_player(&self, game: &Game) -> Player {
+        game.current_player.opponent()
+    }
+
+    /// Whether some winning line could still be completed; a line holding
+    /// both symbols never can
+    fn has_open_line(&self, game: &Game) -> bool {
+        let masks = LineMasks::for_config(&game.config);
+        BitPosition::from_cells(&game.board).has_open_line(&masks)
+    }
+
+    /// Every legal move for the player to move
+    fn legal_moves(&self, game: &Game) -> Vec<Move> {
+        (0..game.board.len())
+            .map(Move::at)
+            .filter(|mv| self.validate_move(game, mv).is_ok())
+            .collect()
+    }
+
+    /// The move that plays the cell at column `x`, row `y` of layer `z`
+    fn move_at(&self, game: &Game, x: usize, y: usize, z: usize) -> Result<Move, TicTacToeError> {
+        game.config
+            .position(x, y, z)
+            .map(Move::at)
+            .ok_or(TicTacToeError::InvalidCoordinates { x, y, z })
+    }
+
+    /// Board index a legal move would fill, without playing it
+    fn target_cell(&self, _game: &Game, mv: &Move) -> usize {
+        mv.position
+    }
+
+    /// Lines of board indices that count as rows on this board
+    fn board_lines(&self, game: &Game) -> Vec<Vec<usize>> {
+        game.config.winning_lines()
+    }
+}
@@ -0,0 +1,121 @@
+use super::{GameRules, Move, Outcome, StandardRules};
+use crate::{BoardConfig, Game, TicTacToeError};
+
+/// Side length of the Qubic cube
+const SIDE: usize = 4;
+
+/// Qubic: four in a row anywhere in a 4x4x4 cube, across all 76 lines.
+///
+/// Cell `(x, y, z)` is stored at index `x + 4 * y + 16 * z` of `Game::board`,
+/// so each layer `z` is a contiguous 4x4 block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QubicRules;
+
+impl GameRules for QubicRules {
+    fn fixed_board(&self) -> Option<BoardConfig> {
+        Some(BoardConfig {
+            width: SIDE,
+            height: SIDE,
+            depth: SIDE,
+            win_length: SIDE,
+        })
+    }
+
+    fn validate_move(&self, game: &Game, mv: &Move) -> Result<(), TicTacToeError> {
+        StandardRules.validate_move(game, mv)
+    }
+
+    fn apply_move(&self, game: &mut Game, mv: &Move) -> usize {
+        StandardRules.apply_move(game, mv)
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        StandardRules.outcome(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameStatus, GameVariant, Player};
+    use linera_base::data_types::Timestamp;
+
+    fn qubic_game() -> Game {
+        let mut game = Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            GameVariant::Qubic,
+            BoardConfig::default(),
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        game
+    }
+
+    fn cell(x: usize, y: usize, z: usize) -> usize {
+        QubicRules.fixed_board().unwrap().position(x, y, z).unwrap()
+    }
+
+    #[test]
+    fn test_qubic_has_76_lines() {
+        let config = QubicRules.fixed_board().unwrap();
+
+        assert_eq!(config.cell_count(), 64);
+        assert_eq!(config.winning_lines().len(), 76);
+    }
+
+    #[test]
+    fn test_coordinates_map_to_layers() {
+        assert_eq!(cell(0, 0, 0), 0);
+        assert_eq!(cell(3, 0, 0), 3);
+        assert_eq!(cell(0, 1, 0), 4);
+        assert_eq!(cell(0, 0, 1), 16);
+        assert_eq!(cell(3, 3, 3), 63);
+        assert_eq!(QubicRules.fixed_board().unwrap().position(4, 0, 0), None);
+    }
+
+    #[test]
+    fn test_vertical_line_through_layers_wins() {
+        let mut game = qubic_game();
+        for z in 0..4 {
+            game.board[cell(1, 2, z)] = Some(Player::O);
+        }
+
+        let line = (0..4).map(|z| cell(1, 2, z)).collect();
+        assert_eq!(
+            QubicRules.outcome(&game),
+            Some(Outcome::Win {
+                symbol: Player::O,
+                line,
+            })
+        );
+    }
+
+    #[test]
+    fn test_space_diagonal_wins() {
+        let mut game = qubic_game();
+        let diagonal: Vec<usize> = (0..4).map(|i| cell(i, 3 - i, 3 - i)).collect();
+        let blockers = [cell(0, 0, 0), cell(1, 0, 0), cell(2, 0, 0)];
+
+        for i in 0..3 {
+            game.make_move(&"alice".to_string(), diagonal[i], Timestamp::from(1)).unwrap();
+            game.make_move(&"bob".to_string(), blockers[i], Timestamp::from(1)).unwrap();
+        }
+        assert_eq!(game.status, GameStatus::InProgress);
+
+        game.make_move(&"alice".to_string(), diagonal[3], Timestamp::from(1)).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::X));
+    }
+
+    #[test]
+    fn test_three_in_a_row_does_not_win() {
+        let mut game = qubic_game();
+        for x in 0..3 {
+            game.board[cell(x, 0, 2)] = Some(Player::X);
+        }
+
+        assert_eq!(QubicRules.outcome(&game), None);
+    }
+}
@@ -0,0 +1,138 @@
+use super::{GameRules, Move, Outcome};
+use crate::bitboard::{BitPosition, LineMasks};
+use crate::{Game, Player, TicTacToeError};
+
+/// Classic m,n,k rules: the first to get `win_length` in a row wins
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardRules;
+
+impl StandardRules {
+    /// Find a winning line whose cells all hold the same symbol
+    pub(crate) fn find_line(game: &Game) -> Option<(Player, Vec<usize>)> {
+        let masks = LineMasks::for_config(&game.config);
+        BitPosition::from_cells(&game.board)
+            .winning_line(&masks)
+            .map(|(symbol, line)| (symbol, line.cells().collect()))
+    }
+}
+
+impl GameRules for StandardRules {
+    fn validate_move(&self, game: &Game, mv: &Move) -> Result<(), TicTacToeError> {
+        if let Some(sub_board) = mv.sub_board {
+            return Err(TicTacToeError::InvalidSubBoard(sub_board));
+        }
+
+        let position = mv.position;
+        if position >= game.board.len() {
+            return Err(TicTacToeError::InvalidPosition(position));
+        }
+
+        if game.board[position].is_some() {
+            return Err(TicTacToeError::InvalidMove(position));
+        }
+
+        Ok(())
+    }
+
+    fn apply_move(&self, game: &mut Game, mv: &Move) -> usize {
+        game.board[mv.position] = Some(mv.mark(game.current_player));
+        mv.position
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        if let Some((symbol, line)) = Self::find_line(game) {
+            Some(Outcome::Win { symbol, line })
+        } else if game.board.iter().all(|cell| cell.is_some()) {
+            Some(Outcome::Draw)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardConfig, GameVariant};
+    use linera_base::data_types::Timestamp;
+
+    fn game_with_board(config: BoardConfig, marks: &[(usize, Player)]) -> Game {
+        let mut game = Game::with_config(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            config,
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        for &(position, player) in marks {
+            game.board[position] = Some(player);
+        }
+        game
+    }
+
+    #[test]
+    fn test_standard_is_default_variant() {
+        let game = game_with_board(BoardConfig::default(), &[]);
+        assert_eq!(game.variant, GameVariant::Standard);
+    }
+
+    #[test]
+    fn test_validate_move() {
+        let game = game_with_board(BoardConfig::default(), &[(4, Player::X)]);
+
+        assert!(StandardRules.validate_move(&game, &Move::at(0)).is_ok());
+        assert!(matches!(
+            StandardRules.validate_move(&game, &Move::at(4)),
+            Err(TicTacToeError::InvalidMove(4))
+        ));
+        assert!(matches!(
+            StandardRules.validate_move(&game, &Move::at(9)),
+            Err(TicTacToeError::InvalidPosition(9))
+        ));
+        assert!(matches!(
+            StandardRules.validate_move(&game, &Move::in_sub_board(0, 0)),
+            Err(TicTacToeError::InvalidSubBoard(0))
+        ));
+    }
+
+    #[test]
+    fn test_apply_move_places_current_player() {
+        let mut game = game_with_board(BoardConfig::default(), &[]);
+
+        assert_eq!(StandardRules.apply_move(&mut game, &Move::at(2)), 2);
+        assert_eq!(game.board[2], Some(Player::X));
+    }
+
+    #[test]
+    fn test_outcome() {
+        let empty = game_with_board(BoardConfig::default(), &[]);
+        assert_eq!(StandardRules.outcome(&empty), None);
+
+        let column = [(1, Player::O), (4, Player::O), (7, Player::O)];
+        let won = game_with_board(BoardConfig::default(), &column);
+        assert_eq!(
+            StandardRules.outcome(&won),
+            Some(Outcome::Win {
+                symbol: Player::O,
+                line: vec![1, 4, 7],
+            })
+        );
+
+        // X O X / X O O / O X X
+        #[rustfmt::skip]
+        let full = [
+            (0, Player::X), (1, Player::O), (2, Player::X),
+            (3, Player::X), (4, Player::O), (5, Player::O),
+            (6, Player::O), (7, Player::X), (8, Player::X),
+        ];
+        let drawn = game_with_board(BoardConfig::default(), &full);
+        assert_eq!(StandardRules.outcome(&drawn), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn test_next_player_alternates() {
+        let game = game_with_board(BoardConfig::default(), &[]);
+        assert_eq!(StandardRules.next_player(&game), Player::O);
+    }
+}
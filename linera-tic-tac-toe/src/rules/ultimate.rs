@@ -0,0 +1,369 @@
+use super::{GameRules, Move, Outcome};
+use crate::{BoardConfig, Game, Player, TicTacToeError};
+use serde::{Deserialize, Serialize};
+
+/// Number of local boards, and of cells in each of them
+const LOCAL_CELLS: usize = 9;
+
+/// Side of the full board, in cells
+const SIDE: usize = 9;
+
+/// Board index of cell `cell` of local board `sub_board`.
+///
+/// The 81 cells live in `Game::board` row by row across the whole 9x9 board,
+/// like any other flat board, so the board's `BoardConfig` describes them.
+/// Local boards and the cells within them are both numbered row by row.
+fn cell_index(sub_board: usize, cell: usize) -> usize {
+    let row = sub_board / 3 * 3 + cell / 3;
+    let column = sub_board % 3 * 3 + cell % 3;
+    row * SIDE + column
+}
+
+/// Meta board state of an Ultimate game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UltimateBoard {
+    /// Winner of each local board
+    pub meta_board: [Option<Player>; LOCAL_CELLS],
+    /// Local board the next move must be played in, or `None` for a free choice
+    pub next_board: Option<usize>,
+}
+
+/// Ultimate Tic Tac Toe: the cell you play decides where your opponent plays next
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UltimateRules;
+
+impl UltimateRules {
+    fn state(game: &Game) -> &UltimateBoard {
+        game.ultimate
+            .as_ref()
+            .expect("Ultimate games always carry a meta board")
+    }
+
+    fn state_mut(game: &mut Game) -> &mut UltimateBoard {
+        game.ultimate
+            .as_mut()
+            .expect("Ultimate games always carry a meta board")
+    }
+
+    fn local_board(game: &Game, sub_board: usize) -> [Option<Player>; LOCAL_CELLS] {
+        std::array::from_fn(|cell| game.board[cell_index(sub_board, cell)])
+    }
+
+    fn three_in_a_row(cells: &[Option<Player>]) -> Option<(Player, Vec<usize>)> {
+        for line in BoardConfig::default().winning_lines() {
+            if let Some(first) = cells[line[0]] {
+                if line.iter().all(|&cell| cells[cell] == Some(first)) {
+                    return Some((first, line));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Whether a local board has been won or filled up
+    fn is_decided(game: &Game, sub_board: usize) -> bool {
+        Self::state(game).meta_board[sub_board].is_some()
+            || Self::local_board(game, sub_board).iter().all(|cell| cell.is_some())
+    }
+}
+
+impl GameRules for UltimateRules {
+    fn fixed_board(&self) -> Option<BoardConfig> {
+        Some(BoardConfig {
+            width: SIDE,
+            height: SIDE,
+            depth: 1,
+            win_length: 3,
+        })
+    }
+
+    fn validate_move(&self, game: &Game, mv: &Move) -> Result<(), TicTacToeError> {
+        let sub_board = mv.sub_board.ok_or(TicTacToeError::MissingSubBoard)?;
+        if sub_board >= LOCAL_CELLS {
+            return Err(TicTacToeError::InvalidSubBoard(sub_board));
+        }
+        if mv.position >= LOCAL_CELLS {
+            return Err(TicTacToeError::InvalidPosition(mv.position));
+        }
+
+        if let Some(required) = Self::state(game).next_board {
+            if required != sub_board {
+                return Err(TicTacToeError::MustPlayInBoard(required));
+            }
+        }
+        if Self::is_decided(game, sub_board) {
+            return Err(TicTacToeError::SubBoardFinished(sub_board));
+        }
+        if Self::local_board(game, sub_board)[mv.position].is_some() {
+            return Err(TicTacToeError::InvalidMove(mv.position));
+        }
+
+        Ok(())
+    }
+
+    fn apply_move(&self, game: &mut Game, mv: &Move) -> usize {
+        let sub_board = mv.sub_board.expect("validated moves carry a sub-board");
+        let index = cell_index(sub_board, mv.position);
+        game.board[index] = Some(mv.mark(game.current_player));
+
+        if let Some((winner, _)) = Self::three_in_a_row(&Self::local_board(game, sub_board)) {
+            Self::state_mut(game).meta_board[sub_board].get_or_insert(winner);
+        }
+
+        // The opponent is sent to the local board matching the cell just played,
+        // unless that board is already decided
+        let next_board = if Self::is_decided(game, mv.position) {
+            None
+        } else {
+            Some(mv.position)
+        };
+        Self::state_mut(game).next_board = next_board;
+
+        index
+    }
+
+    fn has_open_line(&self, game: &Game) -> bool {
+        // A meta line is dead once it holds boards won by both players, or a
+        // board that filled up without a winner
+        let meta_board = &Self::state(game).meta_board;
+        BoardConfig::default().winning_lines().iter().any(|line| {
+            let holds = |symbol| line.iter().any(|&board| meta_board[board] == Some(symbol));
+            let has_drawn_board = line
+                .iter()
+                .any(|&board| meta_board[board].is_none() && Self::is_decided(game, board));
+            !has_drawn_board && !(holds(Player::X) && holds(Player::O))
+        })
+    }
+
+    fn legal_moves(&self, game: &Game) -> Vec<Move> {
+        (0..LOCAL_CELLS)
+            .flat_map(|sub_board| {
+                (0..LOCAL_CELLS).map(move |position| Move::in_sub_board(sub_board, position))
+            })
+            .filter(|mv| self.validate_move(game, mv).is_ok())
+            .collect()
+    }
+
+    fn move_at(&self, game: &Game, x: usize, y: usize, z: usize) -> Result<Move, TicTacToeError> {
+        game.config
+            .position(x, y, z)
+            .ok_or(TicTacToeError::InvalidCoordinates { x, y, z })?;
+        Ok(Move::in_sub_board(y / 3 * 3 + x / 3, y % 3 * 3 + x % 3))
+    }
+
+    fn target_cell(&self, _game: &Game, mv: &Move) -> usize {
+        cell_index(mv.sub_board.expect("legal moves carry a sub-board"), mv.position)
+    }
+
+    fn board_lines(&self, _game: &Game) -> Vec<Vec<usize>> {
+        // Rows of the local boards, since meta lines are won board by board
+        let local_lines = BoardConfig::default().winning_lines();
+        (0..LOCAL_CELLS)
+            .flat_map(|sub_board| {
+                local_lines.iter().map(move |line| {
+                    line.iter().map(|&cell| cell_index(sub_board, cell)).collect()
+                })
+            })
+            .collect()
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        if let Some((symbol, boards)) = Self::three_in_a_row(&Self::state(game).meta_board) {
+            // Every cell of the three local boards making up the meta line
+            let line = boards
+                .iter()
+                .flat_map(|&sub_board| {
+                    (0..LOCAL_CELLS).map(move |cell| cell_index(sub_board, cell))
+                })
+                .collect();
+            Some(Outcome::Win { symbol, line })
+        } else if (0..LOCAL_CELLS).all(|sub_board| Self::is_decided(game, sub_board)) {
+            Some(Outcome::Draw)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameStatus, GameVariant};
+    use linera_base::data_types::Timestamp;
+
+    fn ultimate_game() -> Game {
+        let mut game = Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            GameVariant::Ultimate,
+            BoardConfig::default(),
+            Timestamp::from(0),
+        );
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        game
+    }
+
+    fn play(game: &mut Game, sub_board: usize, position: usize) -> Result<(), TicTacToeError> {
+        let player_id = game
+            .players
+            .iter()
+            .find(|p| p.symbol == game.current_player)
+            .map(|p| p.id.clone())
+            .unwrap();
+        game.play(&player_id, Move::in_sub_board(sub_board, position), Timestamp::from(1))
+    }
+
+    #[test]
+    fn test_ultimate_uses_fixed_board() {
+        let game = ultimate_game();
+
+        assert_eq!(game.board.len(), 81);
+        assert_eq!(game.ultimate, Some(UltimateBoard::default()));
+    }
+
+    #[test]
+    fn test_cells_are_stored_row_major() {
+        let config = UltimateRules.fixed_board().unwrap();
+
+        // The centre of the top-right local board is at column 7, row 1
+        assert_eq!(cell_index(2, 4), config.position(7, 1, 0).unwrap());
+        assert_eq!(cell_index(3, 0), config.position(0, 3, 0).unwrap());
+        assert_eq!(cell_index(8, 8), 80);
+        let mut cells: Vec<_> = (0..81).map(|i| cell_index(i / 9, i % 9)).collect();
+        cells.sort();
+        assert_eq!(cells, (0..81).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_move_requires_sub_board() {
+        let game = ultimate_game();
+
+        assert!(matches!(
+            UltimateRules.validate_move(&game, &Move::at(4)),
+            Err(TicTacToeError::MissingSubBoard)
+        ));
+        assert!(matches!(
+            UltimateRules.validate_move(&game, &Move::in_sub_board(9, 0)),
+            Err(TicTacToeError::InvalidSubBoard(9))
+        ));
+        assert!(matches!(
+            UltimateRules.validate_move(&game, &Move::in_sub_board(0, 9)),
+            Err(TicTacToeError::InvalidPosition(9))
+        ));
+    }
+
+    #[test]
+    fn test_move_sends_opponent_to_matching_board() {
+        let mut game = ultimate_game();
+
+        play(&mut game, 4, 2).unwrap();
+        assert_eq!(game.board[cell_index(4, 2)], Some(Player::X));
+        assert_eq!(game.ultimate.as_ref().unwrap().next_board, Some(2));
+
+        assert!(matches!(
+            play(&mut game, 4, 0),
+            Err(TicTacToeError::MustPlayInBoard(2))
+        ));
+        play(&mut game, 2, 4).unwrap();
+        assert_eq!(game.ultimate.as_ref().unwrap().next_board, Some(4));
+    }
+
+    #[test]
+    fn test_winning_local_board_claims_meta_cell() {
+        let mut game = ultimate_game();
+
+        // X builds the top row of board 0 while O keeps sending X back there
+        for (sub_board, position) in [(0, 1), (1, 0), (0, 2), (2, 0)] {
+            play(&mut game, sub_board, position).unwrap();
+        }
+        assert_eq!(game.ultimate.as_ref().unwrap().meta_board[0], None);
+
+        play(&mut game, 0, 0).unwrap();
+
+        let state = game.ultimate.as_ref().unwrap();
+        assert_eq!(state.meta_board[0], Some(Player::X));
+        // X's cell points back at the board just won, so O may play anywhere
+        assert_eq!(state.next_board, None);
+        assert_eq!(game.status, GameStatus::InProgress);
+    }
+
+    #[test]
+    fn test_decided_board_gives_free_choice() {
+        let mut game = ultimate_game();
+        game.ultimate.as_mut().unwrap().meta_board[3] = Some(Player::O);
+
+        play(&mut game, 0, 3).unwrap();
+        assert_eq!(game.ultimate.as_ref().unwrap().next_board, None);
+        assert!(matches!(
+            play(&mut game, 3, 0),
+            Err(TicTacToeError::SubBoardFinished(3))
+        ));
+        play(&mut game, 8, 8).unwrap();
+    }
+
+    #[test]
+    fn test_meta_board_line_wins_the_game() {
+        let mut game = ultimate_game();
+        {
+            let state = game.ultimate.as_mut().unwrap();
+            state.meta_board[0] = Some(Player::X);
+            state.meta_board[4] = Some(Player::X);
+        }
+        // X is one move away from winning local board 8
+        game.board[cell_index(8, 0)] = Some(Player::X);
+        game.board[cell_index(8, 1)] = Some(Player::X);
+
+        play(&mut game, 8, 2).unwrap();
+
+        match UltimateRules.outcome(&game) {
+            Some(Outcome::Win { symbol, line }) => {
+                assert_eq!(symbol, Player::X);
+                assert_eq!(line.len(), 27);
+                assert!(line.contains(&0) && line.contains(&40) && line.contains(&80));
+            }
+            other => panic!("expected a win, got {:?}", other),
+        }
+        assert_eq!(game.winner, Some(Player::X));
+    }
+
+    #[test]
+    fn test_legal_moves_follow_the_sent_board() {
+        let mut game = ultimate_game();
+        assert_eq!(UltimateRules.legal_moves(&game).len(), 81);
+
+        play(&mut game, 4, 2).unwrap();
+        let moves = UltimateRules.legal_moves(&game);
+        assert_eq!(moves.len(), 9);
+        assert!(moves.iter().all(|mv| mv.sub_board == Some(2)));
+    }
+
+    #[test]
+    fn test_meta_lines_close_when_both_players_hold_boards() {
+        let mut game = ultimate_game();
+        assert!(UltimateRules.has_open_line(&game));
+
+        // X O X / O O X / X X O on the meta board leaves no line for anyone
+        #[rustfmt::skip]
+        let meta = [
+            Some(Player::X), Some(Player::O), Some(Player::X),
+            Some(Player::O), Some(Player::O), Some(Player::X),
+            Some(Player::X), Some(Player::X), None,
+        ];
+        game.ultimate.as_mut().unwrap().meta_board = meta;
+        assert!(UltimateRules.has_open_line(&game));
+
+        // Filling board 8 without a winner closes the last lines
+        #[rustfmt::skip]
+        let drawn = [
+            Some(Player::X), Some(Player::O), Some(Player::X),
+            Some(Player::X), Some(Player::O), Some(Player::O),
+            Some(Player::O), Some(Player::X), Some(Player::X),
+        ];
+        for (cell, mark) in drawn.into_iter().enumerate() {
+            game.board[cell_index(8, cell)] = mark;
+        }
+        assert!(!UltimateRules.has_open_line(&game));
+    }
+}
@@ -0,0 +1,311 @@
+//! Best-of-N match series between two players.
+//!
+//! A match plays ordinary games one after another with the same board, rules
+//! and time control. The first move alternates between the players, and the
+//! match ends as soon as one side has won a majority of `best_of`. Drawn games
+//! still use up one of the `best_of` games, so a match never runs longer than
+//! that; if it runs out of games, whoever won more takes it, and an even score
+//! draws the match.
+
+use crate::{Game, GameId, GameSettings, GameStatus, PlayerId, TicTacToeError};
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a match
+pub type MatchId = u64;
+
+/// A player in a match and the games they have won
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchPlayer {
+    pub id: PlayerId,
+    pub name: String,
+    pub wins: u32,
+}
+
+/// A series of games between the same two players
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Match {
+    pub id: MatchId,
+    /// Maximum number of games; winning a majority of them wins the match
+    pub best_of: u32,
+    /// Board, rules and time control of every game
+    pub settings: GameSettings,
+    /// The creator first; they play X in the first game
+    pub players: Vec<MatchPlayer>,
+    /// Drawn games, which score for neither player
+    pub draws: u32,
+    /// Games of the match, oldest first; the last one is being played
+    pub games: Vec<GameId>,
+    pub status: GameStatus,
+    /// `None` once finished means a drawn match
+    pub winner_id: Option<PlayerId>,
+    pub created_at: Timestamp,
+    pub finished_at: Option<Timestamp>,
+}
+
+impl Match {
+    /// Open a match waiting for an opponent
+    pub fn new(
+        id: MatchId,
+        player_id: PlayerId,
+        player_name: String,
+        best_of: u32,
+        settings: GameSettings,
+        created_at: Timestamp,
+    ) -> Result<Self, TicTacToeError> {
+        if best_of.is_multiple_of(2) {
+            return Err(TicTacToeError::InvalidBestOf(best_of));
+        }
+        settings.validate()?;
+
+        Ok(Self {
+            id,
+            best_of,
+            settings,
+            players: vec![MatchPlayer {
+                id: player_id,
+                name: player_name,
+                wins: 0,
+            }],
+            draws: 0,
+            games: Vec::new(),
+            status: GameStatus::WaitingForPlayer,
+            winner_id: None,
+            created_at,
+            finished_at: None,
+        })
+    }
+
+    /// Games a player must win to take the match
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    /// Add the second player and start the first game as `game_id`
+    pub fn join(
+        &mut self,
+        player_id: PlayerId,
+        player_name: String,
+        game_id: GameId,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        if self.players.len() >= 2 {
+            return Err(TicTacToeError::GameFull);
+        }
+
+        self.players.push(MatchPlayer {
+            id: player_id,
+            name: player_name,
+            wins: 0,
+        });
+        self.status = GameStatus::InProgress;
+        self.next_game(game_id, timestamp)
+    }
+
+    /// Count a finished game of this match, ending the match once a player
+    /// has enough wins or all `best_of` games have been played
+    pub fn record(&mut self, game: &Game) -> Result<(), TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+        if game.status != GameStatus::Finished {
+            return Err(TicTacToeError::GameNotFinished);
+        }
+
+        match &game.winner_id {
+            Some(winner_id) => {
+                let winner = self
+                    .players
+                    .iter_mut()
+                    .find(|player| player.id == *winner_id)
+                    .ok_or(TicTacToeError::PlayerNotInGame)?;
+                winner.wins += 1;
+            }
+            None => self.draws += 1,
+        }
+
+        let wins_needed = self.wins_needed();
+        let leader = match self.players.as_slice() {
+            [creator, opponent] if creator.wins > opponent.wins => Some(creator),
+            [creator, opponent] if opponent.wins > creator.wins => Some(opponent),
+            _ => None,
+        };
+        let decided = leader.is_some_and(|leader| leader.wins >= wins_needed);
+        if decided || self.games.len() >= self.best_of as usize {
+            self.winner_id = leader.map(|leader| leader.id.clone());
+            self.status = GameStatus::Finished;
+            self.finished_at = game.finished_at;
+        }
+        Ok(())
+    }
+
+    /// Start the next game as `game_id`, with the first move going to the
+    /// player who moved second last time
+    pub fn next_game(
+        &mut self,
+        game_id: GameId,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        if self.status != GameStatus::InProgress {
+            return Err(TicTacToeError::GameNotInProgress);
+        }
+
+        let (first, second) = match self.players.as_slice() {
+            [creator, opponent] if self.games.len().is_multiple_of(2) => (creator, opponent),
+            [creator, opponent] => (opponent, creator),
+            _ => return Err(TicTacToeError::PlayerNotInGame),
+        };
+        let mut game = self
+            .settings
+            .new_game(game_id, first.id.clone(), first.name.clone(), timestamp);
+        game.join(second.id.clone(), second.name.clone(), timestamp)?;
+        game.match_id = Some(self.id);
+
+        self.games.push(game_id);
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Player;
+
+    /// A best-of-three match between alice and bob, and its first game
+    fn best_of_three() -> (Match, Game) {
+        let mut series = Match::new(
+            7,
+            "alice".to_string(),
+            "Alice".to_string(),
+            3,
+            GameSettings::default(),
+            Timestamp::from(0),
+        )
+        .unwrap();
+        let game = series
+            .join("bob".to_string(), "Bob".to_string(), 1, Timestamp::from(1))
+            .unwrap();
+        (series, game)
+    }
+
+    /// Finish a game in favour of `winner`, or as an agreed draw
+    fn finish(game: &mut Game, winner: Option<&str>, at: u64) {
+        let (x, o) = (game.players[0].id.clone(), game.players[1].id.clone());
+        match winner {
+            Some(winner) => {
+                let loser = if x == winner { o } else { x };
+                game.resign(&loser, Timestamp::from(at)).unwrap();
+            }
+            None => {
+                game.offer_draw(&x).unwrap();
+                game.respond_to_draw(&o, true, Timestamp::from(at)).unwrap();
+            }
+        }
+    }
+
+    fn first_mover(game: &Game) -> &str {
+        &game.players.iter().find(|p| p.symbol == Player::X).unwrap().id
+    }
+
+    #[test]
+    fn test_first_move_alternates() {
+        let (mut series, first) = best_of_three();
+        let second = series.next_game(2, Timestamp::from(2)).unwrap();
+        let third = series.next_game(3, Timestamp::from(3)).unwrap();
+
+        assert_eq!(series.games, vec![1, 2, 3]);
+        assert_eq!(first_mover(&first), "alice");
+        assert_eq!(first_mover(&second), "bob");
+        assert_eq!(first_mover(&third), "alice");
+        assert_eq!(first.match_id, Some(7));
+        assert_eq!(first.status, GameStatus::InProgress);
+    }
+
+    #[test]
+    fn test_match_ends_at_a_majority() {
+        let (mut series, mut game) = best_of_three();
+        assert_eq!(series.wins_needed(), 2);
+
+        finish(&mut game, Some("alice"), 2);
+        series.record(&game).unwrap();
+
+        let mut game = series.next_game(2, Timestamp::from(3)).unwrap();
+        finish(&mut game, None, 4);
+        series.record(&game).unwrap();
+        assert_eq!(series.draws, 1);
+        assert_eq!(series.status, GameStatus::InProgress);
+
+        let mut game = series.next_game(3, Timestamp::from(5)).unwrap();
+        finish(&mut game, Some("alice"), 6);
+        series.record(&game).unwrap();
+
+        assert_eq!(series.status, GameStatus::Finished);
+        assert_eq!(series.winner_id.as_deref(), Some("alice"));
+        assert_eq!(series.finished_at, Some(Timestamp::from(6)));
+        assert_eq!((series.players[0].wins, series.players[1].wins), (2, 0));
+        assert!(matches!(
+            series.next_game(4, Timestamp::from(7)),
+            Err(TicTacToeError::GameNotInProgress)
+        ));
+    }
+
+    #[test]
+    fn test_draws_use_up_games() {
+        let (mut series, mut game) = best_of_three();
+
+        finish(&mut game, None, 2);
+        series.record(&game).unwrap();
+        let mut game = series.next_game(2, Timestamp::from(3)).unwrap();
+        finish(&mut game, Some("bob"), 4);
+        series.record(&game).unwrap();
+        let mut game = series.next_game(3, Timestamp::from(5)).unwrap();
+        finish(&mut game, None, 6);
+        series.record(&game).unwrap();
+
+        assert_eq!(series.status, GameStatus::Finished);
+        assert_eq!(series.winner_id.as_deref(), Some("bob"));
+        assert_eq!(series.draws, 2);
+        assert_eq!(series.games.len(), 3);
+    }
+
+    #[test]
+    fn test_an_even_score_draws_the_match() {
+        let mut series = Match::new(
+            7,
+            "alice".to_string(),
+            "Alice".to_string(),
+            1,
+            GameSettings::default(),
+            Timestamp::from(0),
+        )
+        .unwrap();
+        let mut game = series
+            .join("bob".to_string(), "Bob".to_string(), 1, Timestamp::from(1))
+            .unwrap();
+
+        finish(&mut game, None, 2);
+        series.record(&game).unwrap();
+
+        assert_eq!(series.status, GameStatus::Finished);
+        assert_eq!(series.winner_id, None);
+        assert_eq!(series.finished_at, Some(Timestamp::from(2)));
+        assert!(matches!(
+            series.next_game(2, Timestamp::from(3)),
+            Err(TicTacToeError::GameNotInProgress)
+        ));
+    }
+
+    #[test]
+    fn test_even_series_are_rejected() {
+        let result = Match::new(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            4,
+            GameSettings::default(),
+            Timestamp::from(0),
+        );
+
+        assert!(matches!(result, Err(TicTacToeError::InvalidBestOf(4))));
+    }
+}
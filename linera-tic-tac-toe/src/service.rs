@@ -1,5 +1,9 @@
 use crate::{
-    ApplicationState, Game, GameId, GameStatus, PlayerId, PlayerStats, Query, QueryResponse,
+    analysis::{self, GameAnalysis},
+    hint::{self, Hint},
+    opening::Continuation,
+    ApplicationState, Game, GameId, GameStatus, Match, MatchId, PlayerId, PlayerStats, Profile,
+    Query, QueryResponse, Relations,
 };
 use async_trait::async_trait;
 use linera_base::data_types::Timestamp;
@@ -8,20 +12,44 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Extended query types for the service
+///
+/// As in `Query`, `viewer` only decides which games are shown.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExtendedQuery {
     /// Basic queries from the contract
     Basic(Query),
-    /// Get games by status
-    GetGamesByStatus { status: GameStatus },
+    /// Get games by status; unlisted and private games only for their participants
+    GetGamesByStatus { status: GameStatus, viewer: Option<PlayerId> },
     /// Get recent games
     GetRecentGames { limit: usize },
-    /// Get games for a specific player
-    GetPlayerGames { player_id: PlayerId },
+    /// Get games of a specific player; unlisted and private games only for their participants
+    GetPlayerGames { player_id: PlayerId, viewer: Option<PlayerId> },
     /// Get leaderboard
     GetLeaderboard { limit: usize },
     /// Get game statistics
     GetGameStatistics,
+    /// Get ranked move suggestions for the player to move, without changing the game
+    GetHint { game_id: GameId, player_id: PlayerId },
+    /// Replay a finished game and judge every move, unless it is hidden from `viewer`
+    GetGameAnalysis { game_id: GameId, viewer: Option<PlayerId> },
+    /// Get how finished classic games continued after the given opening moves
+    GetOpeningStats { moves: Vec<usize> },
+    /// Get a best-of-N match with its score and games
+    GetMatch { match_id: MatchId },
+    /// Get matches waiting for an opponent to join
+    GetOpenMatches,
+    /// Get matches a player has created or joined
+    GetPlayerMatches { player_id: PlayerId },
+    /// Get the profile of a player
+    GetProfile { player_id: PlayerId },
+    /// Get the profile holding a handle, in any case
+    GetProfileByHandle { handle: String },
+    /// Get who a player follows, is followed by and has blocked
+    GetRelations { player_id: PlayerId },
+    /// Get the players who follow a player back
+    GetFriends { player_id: PlayerId },
+    /// Get open challenges sent to a player
+    GetChallenges { player_id: PlayerId },
 }
 
 /// Extended response types
@@ -35,6 +63,22 @@ pub enum ExtendedQueryResponse {
     Leaderboard(Vec<LeaderboardEntry>),
     /// Game statistics
     Statistics(GameStatistics),
+    /// Suggested moves, best first
+    Hints(Vec<Hint>),
+    /// Move-by-move report on a finished game
+    Analysis(GameAnalysis),
+    /// Win, draw and loss counts for each continuation, from the mover's side
+    OpeningStats(Vec<Continuation>),
+    /// A match, if it exists
+    Match(Option<Match>),
+    /// List of matches
+    Matches(Vec<Match>),
+    /// A profile, if it exists
+    Profile(Option<Profile>),
+    /// Follows and blocks of a player
+    Relations(Relations),
+    /// List of players
+    Players(Vec<PlayerId>),
 }
 
 /// Leaderboard entry
@@ -87,10 +131,10 @@ impl Service for TicTacToeService {
                 Ok(ExtendedQueryResponse::Basic(response))
             }
 
-            ExtendedQuery::GetGamesByStatus { status } => {
+            ExtendedQuery::GetGamesByStatus { status, viewer } => {
                 let games = self
                     .state
-                    .get_games_by_status(status)
+                    .get_games_by_status(status, viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get games by status: {}", e))?;
                 Ok(ExtendedQueryResponse::Games(games))
@@ -105,10 +149,10 @@ impl Service for TicTacToeService {
                 Ok(ExtendedQueryResponse::Games(games))
             }
 
-            ExtendedQuery::GetPlayerGames { player_id } => {
+            ExtendedQuery::GetPlayerGames { player_id, viewer } => {
                 let games = self
                     .state
-                    .get_player_games(&player_id)
+                    .get_player_games(&player_id, viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get player games: {}", e))?;
                 Ok(ExtendedQueryResponse::Games(games))
@@ -129,6 +173,111 @@ impl Service for TicTacToeService {
                     .map_err(|e| format!("Failed to generate statistics: {}", e))?;
                 Ok(ExtendedQueryResponse::Statistics(statistics))
             }
+
+            ExtendedQuery::GetHint { game_id, player_id } => {
+                let game = self
+                    .state
+                    .get_game_for(game_id, Some(&player_id))
+                    .await
+                    .map_err(|e| format!("Failed to get game: {}", e))?
+                    .ok_or_else(|| "Game not found".to_string())?;
+                let hints = hint::hints(&game, &player_id)
+                    .map_err(|e| format!("Failed to get hint: {}", e))?;
+                Ok(ExtendedQueryResponse::Hints(hints))
+            }
+
+            ExtendedQuery::GetGameAnalysis { game_id, viewer } => {
+                let game = self
+                    .state
+                    .get_game_for(game_id, viewer.as_ref())
+                    .await
+                    .map_err(|e| format!("Failed to get game: {}", e))?
+                    .ok_or_else(|| "Game not found".to_string())?;
+                let analysis = analysis::analyse(&game)
+                    .map_err(|e| format!("Failed to analyse game: {}", e))?;
+                Ok(ExtendedQueryResponse::Analysis(analysis))
+            }
+
+            ExtendedQuery::GetOpeningStats { moves } => {
+                let stats = self
+                    .state
+                    .get_opening_stats(&moves)
+                    .await
+                    .map_err(|e| format!("Failed to get opening stats: {}", e))?;
+                Ok(ExtendedQueryResponse::OpeningStats(stats))
+            }
+
+            ExtendedQuery::GetMatch { match_id } => {
+                let series = self
+                    .state
+                    .get_match(match_id)
+                    .await
+                    .map_err(|e| format!("Failed to get match: {}", e))?;
+                Ok(ExtendedQueryResponse::Match(series))
+            }
+
+            ExtendedQuery::GetOpenMatches => {
+                let matches = self
+                    .state
+                    .get_open_matches()
+                    .await
+                    .map_err(|e| format!("Failed to get open matches: {}", e))?;
+                Ok(ExtendedQueryResponse::Matches(matches))
+            }
+
+            ExtendedQuery::GetPlayerMatches { player_id } => {
+                let matches = self
+                    .state
+                    .get_player_matches(&player_id)
+                    .await
+                    .map_err(|e| format!("Failed to get player matches: {}", e))?;
+                Ok(ExtendedQueryResponse::Matches(matches))
+            }
+
+            ExtendedQuery::GetProfile { player_id } => {
+                let profile = self
+                    .state
+                    .get_profile(&player_id)
+                    .await
+                    .map_err(|e| format!("Failed to get profile: {}", e))?;
+                Ok(ExtendedQueryResponse::Profile(profile))
+            }
+
+            ExtendedQuery::GetProfileByHandle { handle } => {
+                let profile = self
+                    .state
+                    .get_profile_by_handle(&handle)
+                    .await
+                    .map_err(|e| format!("Failed to get profile: {}", e))?;
+                Ok(ExtendedQueryResponse::Profile(profile))
+            }
+
+            ExtendedQuery::GetRelations { player_id } => {
+                let relations = self
+                    .state
+                    .get_relations(&player_id)
+                    .await
+                    .map_err(|e| format!("Failed to get relations: {}", e))?;
+                Ok(ExtendedQueryResponse::Relations(relations))
+            }
+
+            ExtendedQuery::GetFriends { player_id } => {
+                let relations = self
+                    .state
+                    .get_relations(&player_id)
+                    .await
+                    .map_err(|e| format!("Failed to get relations: {}", e))?;
+                Ok(ExtendedQueryResponse::Players(relations.friends()))
+            }
+
+            ExtendedQuery::GetChallenges { player_id } => {
+                let challenges = self
+                    .state
+                    .get_challenges(&player_id)
+                    .await
+                    .map_err(|e| format!("Failed to get challenges: {}", e))?;
+                Ok(ExtendedQueryResponse::Games(challenges))
+            }
         }
     }
 }
@@ -137,19 +286,19 @@ impl TicTacToeService {
     /// Handle basic queries from the original contract interface
     async fn handle_basic_query(&self, query: Query) -> Result<QueryResponse, String> {
         match query {
-            Query::GetGame { game_id } => {
+            Query::GetGame { game_id, viewer } => {
                 let game = self
                     .state
-                    .get_game(game_id)
+                    .get_game_for(game_id, viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get game: {}", e))?;
                 Ok(QueryResponse::Game(game))
             }
 
-            Query::GetAllGames => {
+            Query::GetAllGames { viewer } => {
                 let games = self
                     .state
-                    .get_all_games()
+                    .get_visible_games(viewer.as_ref())
                     .await
                     .map_err(|e| format!("Failed to get all games: {}", e))?;
                 Ok(QueryResponse::Games(games))
@@ -261,7 +410,7 @@ impl TicTacToeService {
     /// Get games that are waiting for players
     pub async fn get_joinable_games(&self) -> Result<Vec<Game>, Box<dyn std::error::Error>> {
         self.state
-            .get_games_by_status(GameStatus::WaitingForPlayer)
+            .get_games_by_status(GameStatus::WaitingForPlayer, None)
             .await
             .map_err(|e| e.into())
     }
@@ -269,7 +418,7 @@ impl TicTacToeService {
     /// Get active games (in progress)
     pub async fn get_active_games(&self) -> Result<Vec<Game>, Box<dyn std::error::Error>> {
         self.state
-            .get_games_by_status(GameStatus::InProgress)
+            .get_games_by_status(GameStatus::InProgress, None)
             .await
             .map_err(|e| e.into())
     }
@@ -277,17 +426,24 @@ impl TicTacToeService {
     /// Get completed games
     pub async fn get_completed_games(&self) -> Result<Vec<Game>, Box<dyn std::error::Error>> {
         self.state
-            .get_games_by_status(GameStatus::Finished)
+            .get_games_by_status(GameStatus::Finished, None)
             .await
             .map_err(|e| e.into())
     }
 
-    /// Search games by player name
+    /// Search games by player name, or exactly by handle when one matches
     pub async fn search_games_by_player_name(
         &self,
         player_name: &str,
     ) -> Result<Vec<Game>, Box<dyn std::error::Error>> {
-        let all_games = self.state.get_all_games().await?;
+        let all_games = self.state.get_visible_games(None).await?;
+
+        if let Some(profile) = self.state.get_profile_by_handle(player_name).await? {
+            return Ok(all_games
+                .into_iter()
+                .filter(|game| game.players.iter().any(|p| p.id == profile.player_id))
+                .collect());
+        }
         
         let filtered_games: Vec<Game> = all_games
             .into_iter()
@@ -350,6 +506,7 @@ mod tests {
                     wins: 8,
                     losses: 2,
                     draws: 0,
+                    ..PlayerStats::default()
                 },
                 win_rate: 0.8,
                 rank: 0,
@@ -361,6 +518,7 @@ mod tests {
                     wins: 3,
                     losses: 2,
                     draws: 0,
+                    ..PlayerStats::default()
                 },
                 win_rate: 0.6,
                 rank: 0,
@@ -0,0 +1,153 @@
+//! Session keys: short-lived keys a player lets act for them in games.
+//!
+//! A browser can hold a session key and sign moves with it instead of asking
+//! the player's wallet every time. A key only ever covers operations on
+//! existing games, and only until it expires, so it cannot create games,
+//! join them, touch the player's profile or hand out further keys.
+
+use crate::{GameId, Operation, PlayerId, TicTacToeError};
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Games a session key may act on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionScope {
+    /// Every game of the player
+    #[default]
+    AllGames,
+    /// Only the listed games. Each game of a match has its own id, given
+    /// when the game before it ends, so a key meant to play a whole match
+    /// needs `AllGames`.
+    Games(Vec<GameId>),
+}
+
+impl SessionScope {
+    pub fn includes(&self, game_id: GameId) -> bool {
+        match self {
+            SessionScope::AllGames => true,
+            SessionScope::Games(games) => games.contains(&game_id),
+        }
+    }
+}
+
+/// A key a player has allowed to sign game operations for them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKey {
+    /// Chain owner that signs with this key
+    pub key: PlayerId,
+    /// The player the key acts for
+    pub player_id: PlayerId,
+    /// First moment the key is no longer accepted
+    pub expires_at: Timestamp,
+    pub scope: SessionScope,
+}
+
+impl SessionKey {
+    pub fn new(
+        key: PlayerId,
+        player_id: PlayerId,
+        expires_at: Timestamp,
+        scope: SessionScope,
+        now: Timestamp,
+    ) -> Result<Self, TicTacToeError> {
+        if key == player_id || expires_at <= now {
+            return Err(TicTacToeError::InvalidSessionKey);
+        }
+        Ok(Self {
+            key,
+            player_id,
+            expires_at,
+            scope,
+        })
+    }
+
+    /// Check that the key may sign `operation` at `now`
+    pub fn permits(&self, operation: &Operation, now: Timestamp) -> Result<(), TicTacToeError> {
+        if *operation.player_id() != self.player_id {
+            return Err(TicTacToeError::SignerMismatch {
+                claimed: operation.player_id().clone(),
+                signer: self.key.clone(),
+            });
+        }
+        if now >= self.expires_at {
+            return Err(TicTacToeError::SessionKeyExpired);
+        }
+        match operation.game_id() {
+            Some(game_id) if self.scope.includes(game_id) => Ok(()),
+            _ => Err(TicTacToeError::SessionKeyOutOfScope),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(scope: SessionScope) -> SessionKey {
+        SessionKey::new(
+            "browser".to_string(),
+            "alice".to_string(),
+            Timestamp::from(100),
+            scope,
+            Timestamp::from(0),
+        )
+        .unwrap()
+    }
+
+    fn resign(game_id: GameId, player_id: &str) -> Operation {
+        Operation::Resign {
+            game_id,
+            player_id: player_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_keys_act_only_in_scope_and_before_expiry() {
+        let key = session(SessionScope::Games(vec![1]));
+
+        assert!(key.permits(&resign(1, "alice"), Timestamp::from(99)).is_ok());
+        assert!(matches!(
+            key.permits(&resign(2, "alice"), Timestamp::from(99)),
+            Err(TicTacToeError::SessionKeyOutOfScope)
+        ));
+        assert!(matches!(
+            key.permits(&resign(1, "alice"), Timestamp::from(100)),
+            Err(TicTacToeError::SessionKeyExpired)
+        ));
+        assert!(matches!(
+            key.permits(&resign(1, "bob"), Timestamp::from(99)),
+            Err(TicTacToeError::SignerMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_keys_cannot_act_outside_games() {
+        let key = session(SessionScope::AllGames);
+        let operation = Operation::RevokeSessionKey {
+            player_id: "alice".to_string(),
+            key: "browser".to_string(),
+        };
+
+        assert!(matches!(
+            key.permits(&operation, Timestamp::from(0)),
+            Err(TicTacToeError::SessionKeyOutOfScope)
+        ));
+    }
+
+    #[test]
+    fn test_keys_must_be_new_and_unexpired() {
+        let new = |key: &str, expires_at: u64| {
+            SessionKey::new(
+                key.to_string(),
+                "alice".to_string(),
+                Timestamp::from(expires_at),
+                SessionScope::AllGames,
+                Timestamp::from(50),
+            )
+        };
+
+        assert!(new("browser", 51).is_ok());
+        assert!(matches!(new("browser", 50), Err(TicTacToeError::InvalidSessionKey)));
+        assert!(matches!(new("alice", 51), Err(TicTacToeError::InvalidSessionKey)));
+    }
+}
@@ -0,0 +1,215 @@
+//! Follows, friends, blocks and direct challenges.
+//!
+//! Following is one-way; two players who follow each other are friends.
+//! Blocking a player ends follows in both directions and keeps them from
+//! challenging you or joining your open games. A challenge is an ordinary
+//! private game that invites only the challenged player, who can accept it
+//! by taking the seat or decline it.
+
+use crate::{
+    BoardConfig, Game, GameId, GameSettings, GameVariant, PlayerId, RuleOptions, TicTacToeError,
+    TimeControl, Visibility,
+};
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// A player's place in the social graph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Relations {
+    /// Players this player follows
+    pub following: Vec<PlayerId>,
+    /// Players following this player
+    pub followers: Vec<PlayerId>,
+    /// Players this player has blocked
+    pub blocked: Vec<PlayerId>,
+}
+
+impl Relations {
+    pub fn follows(&self, player_id: &PlayerId) -> bool {
+        self.following.contains(player_id)
+    }
+
+    pub fn has_blocked(&self, player_id: &PlayerId) -> bool {
+        self.blocked.contains(player_id)
+    }
+
+    /// Players who follow this player back
+    pub fn friends(&self) -> Vec<PlayerId> {
+        self.following
+            .iter()
+            .filter(|player_id| self.followers.contains(player_id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn follow(&mut self, player_id: &PlayerId) {
+        insert(&mut self.following, player_id);
+    }
+
+    pub fn unfollow(&mut self, player_id: &PlayerId) {
+        self.following.retain(|id| id != player_id);
+    }
+
+    pub fn add_follower(&mut self, player_id: &PlayerId) {
+        insert(&mut self.followers, player_id);
+    }
+
+    pub fn remove_follower(&mut self, player_id: &PlayerId) {
+        self.followers.retain(|id| id != player_id);
+    }
+
+    /// Block a player, dropping follows between the two on this side
+    pub fn block(&mut self, player_id: &PlayerId) {
+        self.unfollow(player_id);
+        self.remove_follower(player_id);
+        insert(&mut self.blocked, player_id);
+    }
+
+    pub fn unblock(&mut self, player_id: &PlayerId) {
+        self.blocked.retain(|id| id != player_id);
+    }
+}
+
+fn insert(players: &mut Vec<PlayerId>, player_id: &PlayerId) {
+    if !players.contains(player_id) {
+        players.push(player_id.clone());
+    }
+}
+
+/// Check that two players may interact, seen from `player_id`'s side
+pub fn check_unblocked(
+    player_id: &PlayerId,
+    relations: &Relations,
+    other_id: &PlayerId,
+    other_relations: &Relations,
+) -> Result<(), TicTacToeError> {
+    if player_id == other_id {
+        return Err(TicTacToeError::CannotTargetYourself);
+    }
+    if relations.has_blocked(other_id) {
+        return Err(TicTacToeError::PlayerBlocked);
+    }
+    if other_relations.has_blocked(player_id) {
+        return Err(TicTacToeError::BlockedByPlayer);
+    }
+    Ok(())
+}
+
+/// Board and rules of a challenge game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeRules {
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+    pub variant: GameVariant,
+    pub options: RuleOptions,
+    /// Base time and increment of each player, `None` for an untimed game
+    pub time_control: Option<TimeControl>,
+}
+
+impl Default for ChallengeRules {
+    fn default() -> Self {
+        let config = BoardConfig::default();
+        Self {
+            width: config.width,
+            height: config.height,
+            win_length: config.win_length,
+            variant: GameVariant::Standard,
+            options: RuleOptions::default(),
+            time_control: None,
+        }
+    }
+}
+
+impl ChallengeRules {
+    /// Check the requested board and time control
+    pub fn settings(&self) -> Result<GameSettings, TicTacToeError> {
+        GameSettings::new(
+            self.variant,
+            self.width,
+            self.height,
+            self.win_length,
+            self.options,
+            self.time_control,
+        )
+    }
+
+    /// A game `player_id` opens for `opponent` alone
+    pub fn new_game(
+        &self,
+        id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        opponent: PlayerId,
+        created_at: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        let mut game = self
+            .settings()?
+            .new_game(id, player_id, player_name, created_at)
+            .with_visibility(Visibility::Private {
+                invited: vec![opponent.clone()],
+                join_code_salt: 0,
+                join_code_hash: None,
+            });
+        game.challenged = Some(opponent);
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutual_follows_are_friends() {
+        let mut alice = Relations::default();
+        alice.follow(&"bob".to_string());
+        alice.follow(&"carol".to_string());
+        alice.add_follower(&"bob".to_string());
+        alice.add_follower(&"dave".to_string());
+
+        assert_eq!(alice.friends(), vec!["bob".to_string()]);
+    }
+
+    #[test]
+    fn test_blocking_drops_follows() {
+        let (alice_id, bob_id) = ("alice".to_string(), "bob".to_string());
+        let mut alice = Relations::default();
+        alice.follow(&bob_id);
+        alice.add_follower(&bob_id);
+        alice.block(&bob_id);
+
+        assert!(!alice.follows(&bob_id));
+        assert!(alice.followers.is_empty());
+        assert!(alice.has_blocked(&bob_id));
+        assert!(matches!(
+            check_unblocked(&bob_id, &Relations::default(), &alice_id, &alice),
+            Err(TicTacToeError::BlockedByPlayer)
+        ));
+        assert!(matches!(
+            check_unblocked(&alice_id, &alice, &bob_id, &Relations::default()),
+            Err(TicTacToeError::PlayerBlocked)
+        ));
+    }
+
+    #[test]
+    fn test_challenges_invite_only_the_opponent() {
+        let game = ChallengeRules::default()
+            .new_game(
+                1,
+                "alice".to_string(),
+                "Alice".to_string(),
+                "bob".to_string(),
+                Timestamp::from(0),
+            )
+            .unwrap();
+
+        assert_eq!(game.challenged.as_deref(), Some("bob"));
+        assert!(game.check_admission(&"bob".to_string(), None).is_ok());
+        assert!(matches!(
+            game.check_admission(&"carol".to_string(), None),
+            Err(TicTacToeError::NotInvited)
+        ));
+        assert!(!game.is_visible_to(None));
+    }
+}
@@ -0,0 +1,353 @@
+//! Perfect-play solver for 3x3 positions.
+//!
+//! Positions are searched with negamax, memoized by a key that is canonical
+//! under the eight rotations and reflections of the square, so the whole game
+//! tree collapses to a few hundred distinct positions.
+
+use crate::bitboard::{BitPosition, LineMasks};
+use crate::{BoardConfig, Game, GameVariant, Player, TicTacToeError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A 3x3 board
+pub type Board = [Option<Player>; 9];
+
+/// The eight symmetries of the square, as maps from new to old cell index
+pub const SYMMETRIES: [[usize; 9]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8], // identity
+    [6, 3, 0, 7, 4, 1, 8, 5, 2], // rotate 90
+    [8, 7, 6, 5, 4, 3, 2, 1, 0], // rotate 180
+    [2, 5, 8, 1, 4, 7, 0, 3, 6], // rotate 270
+    [2, 1, 0, 5, 4, 3, 8, 7, 6], // mirror left-right
+    [6, 7, 8, 3, 4, 5, 0, 1, 2], // mirror top-bottom
+    [0, 3, 6, 1, 4, 7, 2, 5, 8], // main diagonal
+    [8, 5, 2, 7, 4, 1, 6, 3, 0], // anti-diagonal
+];
+
+/// Game-theoretic value of a position for the side to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Value {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Value {
+    /// The same result seen from the other side
+    pub fn flip(self) -> Value {
+        match self {
+            Value::Loss => Value::Win,
+            Value::Draw => Value::Draw,
+            Value::Win => Value::Loss,
+        }
+    }
+}
+
+/// Value of a position together with every move that achieves it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Solution {
+    pub value: Value,
+    /// Empty cells that keep `value`, in board order; empty for finished positions
+    pub best_moves: Vec<usize>,
+}
+
+/// Memoizing 3x3 solver
+#[derive(Debug, Clone)]
+pub struct Solver {
+    /// Under misère rules completing a line loses
+    misere: bool,
+    masks: Rc<LineMasks>,
+    cache: HashMap<u32, Value>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            misere: false,
+            masks: LineMasks::for_config(&BoardConfig::default()),
+            cache: HashMap::new(),
+        }
+    }
+}
+
+impl Solver {
+    /// A solver for standard rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A solver for misère rules, where completing a line loses
+    pub fn misere() -> Self {
+        Self {
+            misere: true,
+            ..Self::default()
+        }
+    }
+
+    /// Solve the current position of a classic 3x3 game
+    pub fn solve(&mut self, game: &Game) -> Result<Solution, TicTacToeError> {
+        if game.variant != GameVariant::Standard
+            || game.config != BoardConfig::default()
+            || game.options.wild
+            || game.options.misere != self.misere
+        {
+            return Err(TicTacToeError::NotSolvable);
+        }
+
+        let mut board = [None; 9];
+        board.copy_from_slice(&game.board);
+        Ok(self.solve_board(&board, game.current_player))
+    }
+
+    /// Solve a board with `to_move` to play
+    pub fn solve_board(&mut self, board: &Board, to_move: Player) -> Solution {
+        let position = BitPosition::from_cells(board);
+        if let Some(value) = self.terminal_value(&position, to_move) {
+            return Solution {
+                value,
+                best_moves: Vec::new(),
+            };
+        }
+
+        let mut value = Value::Loss;
+        let mut best_moves = Vec::new();
+        for (position, child_value) in self.child_values(&position, to_move) {
+            if child_value > value {
+                value = child_value;
+                best_moves.clear();
+            }
+            if child_value == value {
+                best_moves.push(position);
+            }
+        }
+
+        Solution { value, best_moves }
+    }
+
+    /// Value for `to_move` of each legal move, in board order
+    pub fn move_values(&mut self, board: &Board, to_move: Player) -> Vec<(usize, Value)> {
+        self.child_values(&BitPosition::from_cells(board), to_move)
+    }
+
+    /// Value of a position for `to_move`
+    pub fn value(&mut self, board: &Board, to_move: Player) -> Value {
+        self.search(BitPosition::from_cells(board), to_move)
+    }
+
+    /// Number of distinct positions solved so far
+    pub fn cached_positions(&self) -> usize {
+        self.cache.len()
+    }
+
+    fn child_values(&mut self, position: &BitPosition, to_move: Player) -> Vec<(usize, Value)> {
+        if self.terminal_value(position, to_move).is_some() {
+            return Vec::new();
+        }
+
+        let empty = position.empty_cells(&self.masks);
+        empty
+            .cells()
+            .map(|cell| {
+                let mut child = *position;
+                child.place(cell, to_move);
+                (cell, self.search(child, to_move.opponent()).flip())
+            })
+            .collect()
+    }
+
+    /// Memoized negamax
+    fn search(&mut self, position: BitPosition, to_move: Player) -> Value {
+        if let Some(value) = self.terminal_value(&position, to_move) {
+            return value;
+        }
+
+        let key = canonical_key(&position, to_move);
+        if let Some(&value) = self.cache.get(&key) {
+            return value;
+        }
+
+        let mut value = Value::Loss;
+        for cell in position.empty_cells(&self.masks).cells() {
+            let mut child = position;
+            child.place(cell, to_move);
+            value = value.max(self.search(child, to_move.opponent()).flip());
+            if value == Value::Win {
+                break;
+            }
+        }
+        self.cache.insert(key, value);
+        value
+    }
+
+    /// Value of a finished position for `to_move`, or `None` if play goes on
+    fn terminal_value(&self, position: &BitPosition, to_move: Player) -> Option<Value> {
+        if let Some((symbol, _)) = position.winning_line(&self.masks) {
+            // The line was completed by the previous mover
+            let completed_by_mover = symbol == to_move;
+            let mover_wins = completed_by_mover != self.misere;
+            return Some(if mover_wins { Value::Win } else { Value::Loss });
+        }
+
+        position.is_full(&self.masks).then_some(Value::Draw)
+    }
+}
+
+/// Symbol owning a complete line on a 3x3 board
+pub fn line_owner(board: &Board) -> Option<Player> {
+    let masks = LineMasks::for_config(&BoardConfig::default());
+    BitPosition::from_cells(board)
+        .winning_line(&masks)
+        .map(|(symbol, _)| symbol)
+}
+
+/// Base-3 encoding of a board: empty 0, X 1, O 2
+pub fn encode(board: &Board) -> u32 {
+    symmetric_code(&BitPosition::from_cells(board), 0)
+}
+
+/// The board as seen through symmetry `symmetry` of `SYMMETRIES`
+pub fn transform(board: &Board, symmetry: usize) -> Board {
+    SYMMETRIES[symmetry].map(|from| board[from])
+}
+
+/// Base-3 encoding of a position seen through symmetry `symmetry` of
+/// `SYMMETRIES`, without building the transformed board
+fn symmetric_code(position: &BitPosition, symmetry: usize) -> u32 {
+    SYMMETRIES[symmetry].iter().fold(0, |code, &from| {
+        code * 3
+            + match position.symbol_at(from) {
+                None => 0,
+                Some(Player::X) => 1,
+                Some(Player::O) => 2,
+            }
+    })
+}
+
+/// Smallest encoding of the board over all eight symmetries
+pub fn canonical_code(board: &Board) -> u32 {
+    position_code(&BitPosition::from_cells(board))
+}
+
+fn position_code(position: &BitPosition) -> u32 {
+    (0..SYMMETRIES.len())
+        .map(|symmetry| symmetric_code(position, symmetry))
+        .min()
+        .expect("there is always the identity")
+}
+
+/// Cache key of a position: the canonical board plus the side to move
+fn canonical_key(position: &BitPosition, to_move: Player) -> u32 {
+    position_code(position) * 2 + u32::from(to_move == Player::O)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: Option<Player> = Some(Player::X);
+    const O: Option<Player> = Some(Player::O);
+    const E: Option<Player> = None;
+
+    #[test]
+    fn test_empty_board_is_a_draw() {
+        let mut solver = Solver::new();
+        let solution = solver.solve_board(&[None; 9], Player::X);
+
+        assert_eq!(solution.value, Value::Draw);
+        // Every opening move holds the draw
+        assert_eq!(solution.best_moves, (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_corner_opening_must_be_answered_in_the_center() {
+        let mut solver = Solver::new();
+        let board = [X, E, E, E, E, E, E, E, E];
+        let solution = solver.solve_board(&board, Player::O);
+
+        assert_eq!(solution.value, Value::Draw);
+        assert_eq!(solution.best_moves, vec![4]);
+    }
+
+    #[test]
+    fn test_immediate_win_is_found() {
+        let mut solver = Solver::new();
+        let board = [X, X, E, O, O, E, E, E, E];
+        let solution = solver.solve_board(&board, Player::X);
+
+        assert_eq!(solution.value, Value::Win);
+        assert!(solution.best_moves.contains(&2));
+    }
+
+    #[test]
+    fn test_unstoppable_fork_is_a_loss() {
+        let mut solver = Solver::new();
+        // X holds opposite corners and the bottom-left, O cannot cover both threats
+        let board = [X, E, O, E, O, E, X, E, X];
+        let solution = solver.solve_board(&board, Player::O);
+
+        assert_eq!(solution.value, Value::Loss);
+    }
+
+    #[test]
+    fn test_finished_position_has_no_moves() {
+        let mut solver = Solver::new();
+        let board = [X, X, X, O, O, E, E, E, E];
+        let solution = solver.solve_board(&board, Player::O);
+
+        assert_eq!(solution.value, Value::Loss);
+        assert!(solution.best_moves.is_empty());
+    }
+
+    #[test]
+    fn test_misere_empty_board_is_a_draw_and_centre_is_best() {
+        let mut solver = Solver::misere();
+        let solution = solver.solve_board(&[None; 9], Player::X);
+
+        assert_eq!(solution.value, Value::Draw);
+        assert!(solution.best_moves.contains(&4));
+    }
+
+    #[test]
+    fn test_symmetric_boards_share_a_key() {
+        let corner = [X, E, E, E, O, E, E, E, E];
+        let other_corner = [E, E, E, E, O, E, E, E, X];
+
+        assert_eq!(canonical_code(&corner), canonical_code(&other_corner));
+        assert_ne!(canonical_code(&corner), canonical_code(&[E, X, E, E, O, E, E, E, E]));
+    }
+
+    #[test]
+    fn test_canonical_code_is_the_smallest_transformed_encoding() {
+        let board = [X, O, E, E, X, E, E, E, O];
+        let smallest = (0..SYMMETRIES.len())
+            .map(|symmetry| encode(&transform(&board, symmetry)))
+            .min()
+            .unwrap();
+
+        assert_eq!(encode(&board), 3u32.pow(8) + 2 * 3u32.pow(7) + 3u32.pow(4) + 2);
+        assert_eq!(canonical_code(&board), smallest);
+    }
+
+    #[test]
+    fn test_symmetry_reduces_the_search() {
+        let mut solver = Solver::new();
+        solver.solve_board(&[None; 9], Player::X);
+
+        // 4520 non-terminal positions are reachable; far fewer up to symmetry
+        assert!(solver.cached_positions() < 1000);
+    }
+
+    #[test]
+    fn test_solve_rejects_other_boards() {
+        let game = Game::with_config(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            BoardConfig::new(4, 4, 3).unwrap(),
+            linera_base::data_types::Timestamp::from(0),
+        );
+
+        assert!(matches!(Solver::new().solve(&game), Err(TicTacToeError::NotSolvable)));
+    }
+}
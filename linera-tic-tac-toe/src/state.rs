@@ -1,5 +1,12 @@
-use crate::{Game, GameId, GameResult, Player, PlayerStats, PlayerId, TicTacToeError, TicTacToeState};
-use linera_base::data_types::Timestamp;
+use crate::{
+    bot,
+    opening::{self, Continuation, OutcomeTally, PositionKey},
+    profile::{self, Profile},
+    social::{self, ChallengeRules, Relations},
+    Difficulty, Game, GameId, GameResult, GameSettings, Match, MatchId, Move, Operation, Player,
+    PlayerStats, PlayerId, SessionKey, SessionScope, TicTacToeError, TicTacToeState, Visibility,
+};
+use linera_base::{crypto::CryptoHash, data_types::Timestamp};
 use linera_views::{
     common::Context,
     map_view::MapView,
@@ -18,6 +25,20 @@ pub struct ApplicationState {
     pub games: MapView<GameId, Game>,
     /// Map of player statistics
     pub player_stats: MapView<PlayerId, PlayerStats>,
+    /// Results of finished classic games, by canonical position
+    pub opening_tally: MapView<PositionKey, OutcomeTally>,
+    /// Counter for generating unique match IDs
+    pub next_match_id: RegisterView<MatchId>,
+    /// Map of all best-of-N matches
+    pub matches: MapView<MatchId, Match>,
+    /// Player profiles
+    pub profiles: MapView<PlayerId, Profile>,
+    /// Owner of each handle, by lowercase handle
+    pub handles: MapView<String, PlayerId>,
+    /// Session keys, by the player they act for and the key that signs with them
+    pub session_keys: MapView<(PlayerId, PlayerId), SessionKey>,
+    /// Follows and blocks of each player
+    pub relations: MapView<PlayerId, Relations>,
 }
 
 impl ApplicationState {
@@ -26,10 +47,14 @@ impl ApplicationState {
         &mut self,
         player_id: PlayerId,
         player_name: String,
+        settings: GameSettings,
+        visibility: Visibility,
         timestamp: Timestamp,
     ) -> Result<GameId, ViewError> {
         let game_id = self.next_game_id.get();
-        let game = Game::new(game_id, player_id.clone(), player_name, timestamp);
+        let game = settings
+            .new_game(game_id, player_id.clone(), player_name, timestamp)
+            .with_visibility(visibility);
         
         self.games.insert(&game_id, game)?;
         self.next_game_id.set(game_id + 1);
@@ -42,18 +67,167 @@ impl ApplicationState {
         Ok(game_id)
     }
 
+    /// Create a game against the bot
+    pub async fn create_bot_game(
+        &mut self,
+        player_id: PlayerId,
+        player_name: String,
+        difficulty: Difficulty,
+        timestamp: Timestamp,
+    ) -> Result<GameId, ViewError> {
+        let game_id = self.next_game_id.get();
+        let game =
+            Game::against_bot(game_id, player_id.clone(), player_name, difficulty, timestamp);
+
+        self.games.insert(&game_id, game)?;
+        self.next_game_id.set(game_id + 1);
+
+        if !self.player_stats.contains_key(&player_id).await? {
+            self.player_stats.insert(&player_id, PlayerStats::default())?;
+        }
+
+        Ok(game_id)
+    }
+
+    /// Open a best-of-N match
+    pub async fn create_match(
+        &mut self,
+        player_id: PlayerId,
+        player_name: String,
+        best_of: u32,
+        settings: GameSettings,
+        timestamp: Timestamp,
+    ) -> Result<Match, TicTacToeError> {
+        let match_id = self.next_match_id.get();
+        let series =
+            Match::new(match_id, player_id.clone(), player_name, best_of, settings, timestamp)?;
+
+        self.matches.insert(&match_id, series.clone()).map_err(|_| TicTacToeError::MatchNotFound)?;
+        self.next_match_id.set(match_id + 1);
+        self.ensure_player_stats(&player_id).await?;
+        Ok(series)
+    }
+
+    /// Join a match as the second player, starting its first game
+    pub async fn join_match(
+        &mut self,
+        match_id: MatchId,
+        player_id: PlayerId,
+        player_name: String,
+        timestamp: Timestamp,
+    ) -> Result<(Match, Game), TicTacToeError> {
+        let mut series = self.load_match(match_id).await?;
+        self.check_not_blocked_by(&series.players[0].id, &player_id).await?;
+        let game_id = self.next_game_id.get();
+        let game = series.join(player_id.clone(), player_name, game_id, timestamp)?;
+
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        self.next_game_id.set(game_id + 1);
+        self.matches.insert(&match_id, series.clone()).map_err(|_| TicTacToeError::MatchNotFound)?;
+        self.ensure_player_stats(&player_id).await?;
+        Ok((series, game))
+    }
+
+    /// Get a specific match
+    pub async fn get_match(&self, match_id: MatchId) -> Result<Option<Match>, ViewError> {
+        self.matches.get(&match_id).await
+    }
+
+    /// Get matches still waiting for an opponent, oldest first
+    pub async fn get_open_matches(&self) -> Result<Vec<Match>, ViewError> {
+        let mut open = Vec::new();
+        for index in self.matches.indices().await? {
+            if let Some(series) = self.matches.get(&index).await? {
+                if series.status == crate::GameStatus::WaitingForPlayer {
+                    open.push(series);
+                }
+            }
+        }
+        Ok(open)
+    }
+
+    /// Get matches a player has created or joined, oldest first
+    pub async fn get_player_matches(&self, player_id: &PlayerId) -> Result<Vec<Match>, ViewError> {
+        let mut player_matches = Vec::new();
+        for index in self.matches.indices().await? {
+            if let Some(series) = self.matches.get(&index).await? {
+                if series.players.iter().any(|p| p.id == *player_id) {
+                    player_matches.push(series);
+                }
+            }
+        }
+        Ok(player_matches)
+    }
+
+    /// Load a match that must exist
+    async fn load_match(&self, match_id: MatchId) -> Result<Match, TicTacToeError> {
+        self.matches.get(&match_id).await
+            .map_err(|_| TicTacToeError::MatchNotFound)?
+            .ok_or(TicTacToeError::MatchNotFound)
+    }
+
+    /// Start empty stats for a player seen for the first time
+    async fn ensure_player_stats(&mut self, player_id: &PlayerId) -> Result<(), TicTacToeError> {
+        if !self.player_stats.contains_key(player_id).await.unwrap_or(false) {
+            self.player_stats.insert(player_id, PlayerStats::default())
+                .map_err(|_| TicTacToeError::GameNotFound)?;
+        }
+        Ok(())
+    }
+
+    /// Count a finished match game, then start the next game or settle the
+    /// match; returns the next game, if any
+    async fn record_match_game(
+        &mut self,
+        match_id: MatchId,
+        game: &Game,
+    ) -> Result<Option<Game>, TicTacToeError> {
+        let mut series = self.load_match(match_id).await?;
+        series.record(game)?;
+
+        let mut next = None;
+        if series.status == crate::GameStatus::Finished {
+            for player in &series.players {
+                let result = match &series.winner_id {
+                    Some(winner_id) if *winner_id == player.id => GameResult::Win,
+                    Some(_) => GameResult::Loss,
+                    None => GameResult::Draw,
+                };
+                let mut stats = self.get_player_stats(&player.id).await
+                    .map_err(|_| TicTacToeError::GameNotFound)?;
+                stats.update_after_match(result);
+                self.player_stats.insert(&player.id, stats)
+                    .map_err(|_| TicTacToeError::GameNotFound)?;
+            }
+        } else {
+            let game_id = self.next_game_id.get();
+            let timestamp = game.finished_at.unwrap_or(game.created_at);
+            let game = series.next_game(game_id, timestamp)?;
+            self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+            self.next_game_id.set(game_id + 1);
+            next = Some(game);
+        }
+
+        self.matches.insert(&match_id, series).map_err(|_| TicTacToeError::MatchNotFound)?;
+        Ok(next)
+    }
+
     /// Join an existing game
     pub async fn join_game(
         &mut self,
         game_id: GameId,
         player_id: PlayerId,
         player_name: String,
+        join_code: Option<String>,
+        timestamp: Timestamp,
     ) -> Result<(), TicTacToeError> {
         let mut game = self.games.get(&game_id).await
             .map_err(|_| TicTacToeError::GameNotFound)?
             .ok_or(TicTacToeError::GameNotFound)?;
         
-        game.add_player(player_id.clone(), player_name)?;
+        game.check_admission(&player_id, join_code.as_deref())?;
+        self.check_not_blocked_by(&game.players[0].id, &player_id).await?;
+        game.join(player_id.clone(), player_name, timestamp)?;
         self.games.insert(&game_id, game).map_err(|_| TicTacToeError::GameNotFound)?;
         
         // Initialize player stats if they don't exist
@@ -65,64 +239,577 @@ impl ApplicationState {
         Ok(())
     }
 
-    /// Make a move in a game
+    /// Make a move in a game; a move that ends a match game also returns the
+    /// next game of the match
     pub async fn make_move(
         &mut self,
         game_id: GameId,
         player_id: PlayerId,
-        position: usize,
+        mv: Move,
         timestamp: Timestamp,
-    ) -> Result<Game, TicTacToeError> {
+    ) -> Result<(Game, Option<Game>), TicTacToeError> {
         let mut game = self.games.get(&game_id).await
             .map_err(|_| TicTacToeError::GameNotFound)?
             .ok_or(TicTacToeError::GameNotFound)?;
         
         let was_finished = game.status == crate::GameStatus::Finished;
-        game.make_move(&player_id, position, timestamp)?;
+        game.play(&player_id, mv, timestamp)?;
+        // In bot games the bot answers within the same operation
+        bot::reply(&mut game, timestamp)?;
         
-        // If game just finished, update player stats
+        // If game just finished, update player stats and the opening explorer
+        let mut next = None;
         if !was_finished && game.status == crate::GameStatus::Finished {
-            self.update_player_stats_after_game(&game).await?;
+            next = self.record_finished_game(&game).await?;
         }
         
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok((game, next))
+    }
+
+    /// Ask the opponent to take back the last move
+    pub async fn request_takeback(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+    ) -> Result<Game, TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.request_takeback(&player_id)?;
         self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
         Ok(game)
     }
 
+    /// Accept a pending takeback request, undoing the last move
+    pub async fn accept_takeback(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+    ) -> Result<Game, TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.accept_takeback(&player_id)?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok(game)
+    }
+
+    /// Win a timed game on the opponent's expired clock, also returning the
+    /// next game of a match
+    pub async fn claim_timeout(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        timestamp: Timestamp,
+    ) -> Result<(Game, Option<Game>), TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.claim_timeout(&player_id, timestamp)?;
+        let next = self.record_finished_game(&game).await?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok((game, next))
+    }
+
+    /// Resign a game, finishing it in the opponent's favour, also returning
+    /// the next game of a match
+    pub async fn resign(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        timestamp: Timestamp,
+    ) -> Result<(Game, Option<Game>), TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.resign(&player_id, timestamp)?;
+        let next = self.record_finished_game(&game).await?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok((game, next))
+    }
+
+    /// Offer the opponent a draw
+    pub async fn offer_draw(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+    ) -> Result<Game, TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.offer_draw(&player_id)?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok(game)
+    }
+
+    /// Accept or decline a pending draw offer; an accepted draw in a match
+    /// also returns the next game of the match
+    pub async fn respond_to_draw(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        accept: bool,
+        timestamp: Timestamp,
+    ) -> Result<(Game, Option<Game>), TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.respond_to_draw(&player_id, accept, timestamp)?;
+        let mut next = None;
+        if game.status == crate::GameStatus::Finished {
+            next = self.record_finished_game(&game).await?;
+        }
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok((game, next))
+    }
+
+    /// Offer a rematch of a finished game; the bot takes every rematch it is
+    /// offered, so in bot games the new game is returned as well
+    pub async fn offer_rematch(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        timestamp: Timestamp,
+    ) -> Result<(Game, Option<Game>), TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.offer_rematch(&player_id)?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+
+        if game.bot.is_some() && !bot::is_bot(&player_id) {
+            let bot_id = bot::BOT_PLAYER_ID.to_string();
+            let (game, rematch) = self.accept_rematch(game_id, bot_id, timestamp).await?;
+            return Ok((game, Some(rematch)));
+        }
+        Ok((game, None))
+    }
+
+    /// Accept a rematch offer, creating the new game with symbols swapped
+    pub async fn accept_rematch(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        timestamp: Timestamp,
+    ) -> Result<(Game, Game), TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        let rematch_id = self.next_game_id.get();
+        let mut rematch = game.accept_rematch(&player_id, rematch_id, timestamp)?;
+        // A bot that now plays X opens straight away
+        bot::reply(&mut rematch, timestamp)?;
+
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        self.games.insert(&rematch_id, rematch.clone())
+            .map_err(|_| TicTacToeError::GameNotFound)?;
+        self.next_game_id.set(rematch_id + 1);
+        Ok((game, rematch))
+    }
+
+    /// Load a game that must exist
+    async fn load_game(&self, game_id: GameId) -> Result<Game, TicTacToeError> {
+        self.games.get(&game_id).await
+            .map_err(|_| TicTacToeError::GameNotFound)?
+            .ok_or(TicTacToeError::GameNotFound)
+    }
+
     /// Get a specific game
     pub async fn get_game(&self, game_id: GameId) -> Result<Option<Game>, ViewError> {
-        self.games.get(&game_id).await
+        match self.games.get(&game_id).await? {
+            Some(game) => Ok(Some(self.with_profile_names(game).await?)),
+            None => Ok(None),
+        }
     }
 
     /// Get all games
     pub async fn get_all_games(&self) -> Result<Vec<Game>, ViewError> {
         let mut games = Vec::new();
         for index in self.games.indices().await? {
-            if let Some(game) = self.games.get(&index).await? {
+            if let Some(game) = self.get_game(index).await? {
                 games.push(game);
             }
         }
         Ok(games)
     }
 
+    /// Show each player under their profile's display name, if they have one
+    pub async fn with_profile_names(&self, mut game: Game) -> Result<Game, ViewError> {
+        for player in &mut game.players {
+            if let Some(profile) = self.profiles.get(&player.id).await? {
+                player.name = profile.display_name;
+            }
+        }
+        Ok(game)
+    }
+
+    /// Get a game, unless it is private and hidden from `viewer`
+    pub async fn get_game_for(
+        &self,
+        game_id: GameId,
+        viewer: Option<&PlayerId>,
+    ) -> Result<Option<Game>, ViewError> {
+        Ok(self.get_game(game_id).await?.filter(|game| game.is_reachable_by(viewer)))
+    }
+
+    /// Get listed games, plus the unlisted and private games `viewer` is part of
+    pub async fn get_visible_games(&self, viewer: Option<&PlayerId>) -> Result<Vec<Game>, ViewError> {
+        let mut games = self.get_all_games().await?;
+        games.retain(|game| game.is_visible_to(viewer));
+        Ok(games)
+    }
+
+    /// Register a profile under a handle nobody else holds, in any case
+    pub async fn register_profile(
+        &mut self,
+        player_id: PlayerId,
+        handle: String,
+        display_name: String,
+        avatar_hash: Option<CryptoHash>,
+        timestamp: Timestamp,
+    ) -> Result<Profile, TicTacToeError> {
+        if self.load_profile(&player_id).await.is_ok() {
+            return Err(TicTacToeError::ProfileExists);
+        }
+        let key = self.free_handle_key(&handle, &player_id).await?;
+
+        let profile = Profile::new(player_id.clone(), handle, display_name, avatar_hash, timestamp)?;
+        self.handles.insert(&key, player_id.clone()).map_err(|_| TicTacToeError::ProfileNotFound)?;
+        self.profiles.insert(&player_id, profile.clone())
+            .map_err(|_| TicTacToeError::ProfileNotFound)?;
+        Ok(profile)
+    }
+
+    /// Move a profile to a new handle, releasing the old one
+    pub async fn rename_profile(
+        &mut self,
+        player_id: PlayerId,
+        handle: String,
+    ) -> Result<Profile, TicTacToeError> {
+        let mut profile = self.load_profile(&player_id).await?;
+        let key = self.free_handle_key(&handle, &player_id).await?;
+
+        let old_key = profile::handle_key(&profile.handle)?;
+        self.handles.remove(&old_key).map_err(|_| TicTacToeError::ProfileNotFound)?;
+        self.handles.insert(&key, player_id.clone()).map_err(|_| TicTacToeError::ProfileNotFound)?;
+        profile.handle = handle;
+        self.profiles.insert(&player_id, profile.clone())
+            .map_err(|_| TicTacToeError::ProfileNotFound)?;
+        Ok(profile)
+    }
+
+    /// Change the display name and avatar of a profile
+    pub async fn update_profile(
+        &mut self,
+        player_id: PlayerId,
+        display_name: String,
+        avatar_hash: Option<CryptoHash>,
+    ) -> Result<Profile, TicTacToeError> {
+        let mut profile = self.load_profile(&player_id).await?;
+        profile::validate_display_name(&display_name)?;
+
+        profile.display_name = display_name;
+        profile.avatar_hash = avatar_hash;
+        self.profiles.insert(&player_id, profile.clone())
+            .map_err(|_| TicTacToeError::ProfileNotFound)?;
+        Ok(profile)
+    }
+
+    /// Get the profile of a player
+    pub async fn get_profile(&self, player_id: &PlayerId) -> Result<Option<Profile>, ViewError> {
+        self.profiles.get(player_id).await
+    }
+
+    /// Get the profile holding a handle, in any case
+    pub async fn get_profile_by_handle(&self, handle: &str) -> Result<Option<Profile>, ViewError> {
+        match self.handles.get(&handle.to_ascii_lowercase()).await? {
+            Some(player_id) => self.profiles.get(&player_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Load a profile that must exist
+    async fn load_profile(&self, player_id: &PlayerId) -> Result<Profile, TicTacToeError> {
+        self.profiles.get(player_id).await
+            .map_err(|_| TicTacToeError::ProfileNotFound)?
+            .ok_or(TicTacToeError::ProfileNotFound)
+    }
+
+    /// Index key of a handle that is free, or already held by `player_id`
+    async fn free_handle_key(
+        &self,
+        handle: &str,
+        player_id: &PlayerId,
+    ) -> Result<String, TicTacToeError> {
+        let key = profile::handle_key(handle)?;
+        let owner = self.handles.get(&key).await.map_err(|_| TicTacToeError::ProfileNotFound)?;
+        match owner {
+            Some(owner) if owner != *player_id => Err(TicTacToeError::HandleTaken(handle.to_string())),
+            _ => Ok(key),
+        }
+    }
+
+    /// Let `key` sign game operations for `player_id`, replacing any earlier
+    /// grant of the same key by the same player. Grants are kept per player,
+    /// so a key one player authorized never stops another from using it.
+    pub async fn authorize_session_key(
+        &mut self,
+        player_id: PlayerId,
+        key: PlayerId,
+        expires_at: Timestamp,
+        scope: SessionScope,
+        timestamp: Timestamp,
+    ) -> Result<SessionKey, TicTacToeError> {
+        let session =
+            SessionKey::new(key.clone(), player_id.clone(), expires_at, scope, timestamp)?;
+        self.session_keys.insert(&(player_id, key), session.clone())
+            .map_err(|_| TicTacToeError::SessionKeyNotFound)?;
+        Ok(session)
+    }
+
+    /// Withdraw one of the player's session keys
+    pub async fn revoke_session_key(
+        &mut self,
+        player_id: &PlayerId,
+        key: &PlayerId,
+    ) -> Result<(), TicTacToeError> {
+        let index = (player_id.clone(), key.clone());
+        match self.load_session_key(player_id, key).await? {
+            Some(_) => self.session_keys.remove(&index)
+                .map_err(|_| TicTacToeError::SessionKeyNotFound),
+            None => Err(TicTacToeError::SessionKeyNotFound),
+        }
+    }
+
+    /// Check that `signer` may perform `operation`, either as the player it
+    /// acts for or through one of that player's session keys
+    pub async fn authorize(
+        &self,
+        operation: &Operation,
+        signer: Option<&PlayerId>,
+        timestamp: Timestamp,
+    ) -> Result<(), TicTacToeError> {
+        let error = match operation.authorize(signer) {
+            Ok(()) => return Ok(()),
+            Err(error @ TicTacToeError::SignerMismatch { .. }) => error,
+            Err(error) => return Err(error),
+        };
+        match signer {
+            Some(signer) => match self.load_session_key(operation.player_id(), signer).await? {
+                Some(session) => session.permits(operation, timestamp),
+                None => Err(error),
+            },
+            None => Err(error),
+        }
+    }
+
+    async fn load_session_key(
+        &self,
+        player_id: &PlayerId,
+        key: &PlayerId,
+    ) -> Result<Option<SessionKey>, TicTacToeError> {
+        self.session_keys.get(&(player_id.clone(), key.clone())).await
+            .map_err(|_| TicTacToeError::SessionKeyNotFound)
+    }
+
+    /// Follow a player who has not blocked you
+    pub async fn follow(&mut self, player_id: PlayerId, target: PlayerId) -> Result<(), TicTacToeError> {
+        let (mut mine, mut theirs) = self.load_relation_pair(&player_id, &target).await?;
+        social::check_unblocked(&player_id, &mine, &target, &theirs)?;
+
+        mine.follow(&target);
+        theirs.add_follower(&player_id);
+        self.save_relation_pair(&player_id, mine, &target, theirs)
+    }
+
+    /// Stop following a player
+    pub async fn unfollow(&mut self, player_id: PlayerId, target: PlayerId) -> Result<(), TicTacToeError> {
+        let (mut mine, mut theirs) = self.load_relation_pair(&player_id, &target).await?;
+
+        mine.unfollow(&target);
+        theirs.remove_follower(&player_id);
+        self.save_relation_pair(&player_id, mine, &target, theirs)
+    }
+
+    /// Block a player, ending follows between the two in both directions
+    pub async fn block(&mut self, player_id: PlayerId, target: PlayerId) -> Result<(), TicTacToeError> {
+        let (mut mine, mut theirs) = self.load_relation_pair(&player_id, &target).await?;
+
+        mine.block(&target);
+        theirs.unfollow(&player_id);
+        theirs.remove_follower(&player_id);
+        self.save_relation_pair(&player_id, mine, &target, theirs)
+    }
+
+    /// Lift a block
+    pub async fn unblock(&mut self, player_id: PlayerId, target: PlayerId) -> Result<(), TicTacToeError> {
+        let mut mine = self.get_relations(&player_id).await
+            .map_err(|_| TicTacToeError::GameNotFound)?;
+        mine.unblock(&target);
+        self.relations.insert(&player_id, mine).map_err(|_| TicTacToeError::GameNotFound)
+    }
+
+    /// Get the follows and blocks of a player
+    pub async fn get_relations(&self, player_id: &PlayerId) -> Result<Relations, ViewError> {
+        Ok(self.relations.get(player_id).await?.unwrap_or_default())
+    }
+
+    /// Send `opponent` a challenge, as a new game only they may join
+    pub async fn challenge(
+        &mut self,
+        player_id: PlayerId,
+        player_name: String,
+        opponent: PlayerId,
+        rules: ChallengeRules,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        let (mine, theirs) = self.load_relation_pair(&player_id, &opponent).await?;
+        social::check_unblocked(&player_id, &mine, &opponent, &theirs)?;
+
+        let game_id = self.next_game_id.get();
+        let game = rules.new_game(game_id, player_id.clone(), player_name, opponent, timestamp)?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        self.next_game_id.set(game_id + 1);
+        self.ensure_player_stats(&player_id).await?;
+        Ok(game)
+    }
+
+    /// Accept a challenge by taking its open seat
+    pub async fn accept_challenge(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        player_name: String,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        let game = self.load_game(game_id).await?;
+        if game.challenged.as_ref() != Some(&player_id) {
+            return Err(TicTacToeError::NotChallenged);
+        }
+        if game.status != crate::GameStatus::WaitingForPlayer {
+            return Err(TicTacToeError::ChallengeClosed);
+        }
+
+        self.join_game(game_id, player_id, player_name, None, timestamp).await?;
+        self.load_game(game_id).await
+    }
+
+    /// Decline a challenge, ending its game; nobody's record changes
+    pub async fn decline_challenge(
+        &mut self,
+        game_id: GameId,
+        player_id: PlayerId,
+        timestamp: Timestamp,
+    ) -> Result<Game, TicTacToeError> {
+        let mut game = self.load_game(game_id).await?;
+        game.decline_challenge(&player_id, timestamp)?;
+        self.games.insert(&game_id, game.clone()).map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok(game)
+    }
+
+    /// Challenges sent to a player that are still open
+    pub async fn get_challenges(&self, player_id: &PlayerId) -> Result<Vec<Game>, ViewError> {
+        let waiting = self
+            .get_games_by_status(crate::GameStatus::WaitingForPlayer, Some(player_id))
+            .await?;
+        Ok(waiting
+            .into_iter()
+            .filter(|game| game.challenged.as_ref() == Some(player_id))
+            .collect())
+    }
+
+    /// Check that `owner` has not blocked `player_id`
+    async fn check_not_blocked_by(
+        &self,
+        owner: &PlayerId,
+        player_id: &PlayerId,
+    ) -> Result<(), TicTacToeError> {
+        let relations = self.get_relations(owner).await.map_err(|_| TicTacToeError::GameNotFound)?;
+        if relations.has_blocked(player_id) {
+            return Err(TicTacToeError::BlockedByPlayer);
+        }
+        Ok(())
+    }
+
+    async fn load_relation_pair(
+        &self,
+        player_id: &PlayerId,
+        other_id: &PlayerId,
+    ) -> Result<(Relations, Relations), TicTacToeError> {
+        if player_id == other_id {
+            return Err(TicTacToeError::CannotTargetYourself);
+        }
+        let mine = self.get_relations(player_id).await.map_err(|_| TicTacToeError::GameNotFound)?;
+        let theirs = self.get_relations(other_id).await.map_err(|_| TicTacToeError::GameNotFound)?;
+        Ok((mine, theirs))
+    }
+
+    fn save_relation_pair(
+        &mut self,
+        player_id: &PlayerId,
+        mine: Relations,
+        other_id: &PlayerId,
+        theirs: Relations,
+    ) -> Result<(), TicTacToeError> {
+        self.relations.insert(player_id, mine).map_err(|_| TicTacToeError::GameNotFound)?;
+        self.relations.insert(other_id, theirs).map_err(|_| TicTacToeError::GameNotFound)
+    }
+
     /// Get player statistics
     pub async fn get_player_stats(&self, player_id: &PlayerId) -> Result<PlayerStats, ViewError> {
         Ok(self.player_stats.get(player_id).await?.unwrap_or_default())
     }
 
+    /// Book-keeping for a game that just finished; returns the next game when
+    /// the game was part of a match that goes on
+    async fn record_finished_game(&mut self, game: &Game) -> Result<Option<Game>, TicTacToeError> {
+        // Match games count once, towards the match
+        if game.match_id.is_none() {
+            self.update_player_stats_after_game(game).await?;
+        }
+
+        // Bot games say nothing about how people play
+        if opening::is_classic(game) && game.bot.is_none() {
+            for key in opening::game_positions(game) {
+                let mut tally = self.opening_tally.get(&key).await
+                    .map_err(|_| TicTacToeError::GameNotFound)?
+                    .unwrap_or_default();
+                tally.record(game.winner);
+                self.opening_tally.insert(&key, tally)
+                    .map_err(|_| TicTacToeError::GameNotFound)?;
+            }
+        }
+
+        match game.match_id {
+            Some(match_id) => self.record_match_game(match_id, game).await,
+            None => Ok(None),
+        }
+    }
+
+    /// How finished classic games continued after the opening `moves`,
+    /// most played continuation first
+    pub async fn get_opening_stats(
+        &self,
+        moves: &[usize],
+    ) -> Result<Vec<Continuation>, TicTacToeError> {
+        let (mover, children) = opening::continuations(moves)?;
+
+        let mut stats = Vec::new();
+        for (position, key) in children {
+            let tally = self.opening_tally.get(&key).await
+                .map_err(|_| TicTacToeError::GameNotFound)?
+                .unwrap_or_default();
+            if tally.games() > 0 {
+                stats.push(Continuation::from_tally(position, mover, &tally));
+            }
+        }
+        stats.sort_by_key(|c| std::cmp::Reverse(c.wins + c.draws + c.losses));
+        Ok(stats)
+    }
+
     /// Update player statistics after a game finishes
     async fn update_player_stats_after_game(&mut self, game: &Game) -> Result<(), TicTacToeError> {
         if game.players.len() != 2 {
             return Ok(()); // Can't update stats for incomplete games
         }
 
+        if game.bot.is_some() {
+            return self.update_player_stats_after_bot_game(game).await;
+        }
+
         let player1 = &game.players[0];
         let player2 = &game.players[1];
 
-        // Determine results for each player
-        let (result1, result2) = match game.winner {
-            Some(winner) => {
-                if player1.symbol == winner {
+        // Determine results for each player, crediting the winner by identity
+        // since wild and misère games decouple the winner from the line's symbol
+        let (result1, result2) = match &game.winner_id {
+            Some(winner_id) => {
+                if player1.id == *winner_id {
                     (GameResult::Win, GameResult::Loss)
                 } else {
                     (GameResult::Loss, GameResult::Win)
@@ -148,12 +835,37 @@ impl ApplicationState {
         Ok(())
     }
 
-    /// Get games by status
-    pub async fn get_games_by_status(&self, status: crate::GameStatus) -> Result<Vec<Game>, ViewError> {
+    /// Record a finished bot game for the human player only, apart from ranked play
+    async fn update_player_stats_after_bot_game(&mut self, game: &Game) -> Result<(), TicTacToeError> {
+        let Some(human) = game.players.iter().find(|p| !bot::is_bot(&p.id)) else {
+            return Ok(());
+        };
+
+        let result = match &game.winner_id {
+            Some(winner_id) if *winner_id == human.id => GameResult::Win,
+            Some(_) => GameResult::Loss,
+            None => GameResult::Draw,
+        };
+
+        let mut stats = self.get_player_stats(&human.id).await
+            .map_err(|_| TicTacToeError::GameNotFound)?;
+        stats.update_after_bot_game(result);
+        self.player_stats.insert(&human.id, stats)
+            .map_err(|_| TicTacToeError::GameNotFound)?;
+
+        Ok(())
+    }
+
+    /// Get games by status, leaving out games hidden from `viewer`
+    pub async fn get_games_by_status(
+        &self,
+        status: crate::GameStatus,
+        viewer: Option<&PlayerId>,
+    ) -> Result<Vec<Game>, ViewError> {
         let mut filtered_games = Vec::new();
         for index in self.games.indices().await? {
-            if let Some(game) = self.games.get(&index).await? {
-                if game.status == status {
+            if let Some(game) = self.get_game(index).await? {
+                if game.status == status && game.is_visible_to(viewer) {
                     filtered_games.push(game);
                 }
             }
@@ -161,9 +873,9 @@ impl ApplicationState {
         Ok(filtered_games)
     }
 
-    /// Get recent games (last N games)
+    /// Get recent listed games (last N games)
     pub async fn get_recent_games(&self, limit: usize) -> Result<Vec<Game>, ViewError> {
-        let mut all_games = self.get_all_games().await?;
+        let mut all_games = self.get_visible_games(None).await?;
         
         // Sort by creation time (most recent first)
         all_games.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -174,12 +886,16 @@ impl ApplicationState {
         Ok(all_games)
     }
 
-    /// Get games for a specific player
-    pub async fn get_player_games(&self, player_id: &PlayerId) -> Result<Vec<Game>, ViewError> {
+    /// Get games of a specific player, leaving out games hidden from `viewer`
+    pub async fn get_player_games(
+        &self,
+        player_id: &PlayerId,
+        viewer: Option<&PlayerId>,
+    ) -> Result<Vec<Game>, ViewError> {
         let mut player_games = Vec::new();
         for index in self.games.indices().await? {
-            if let Some(game) = self.games.get(&index).await? {
-                if game.players.iter().any(|p| p.id == *player_id) {
+            if let Some(game) = self.get_game(index).await? {
+                if game.players.iter().any(|p| p.id == *player_id) && game.is_visible_to(viewer) {
                     player_games.push(game);
                 }
             }
@@ -197,12 +913,26 @@ impl View<Context> for ApplicationState {
     async fn load(context: Context) -> Result<Self, ViewError> {
         let next_game_id = RegisterView::load(context.clone()).await?;
         let games = MapView::load(context.clone()).await?;
-        let player_stats = MapView::load(context).await?;
+        let player_stats = MapView::load(context.clone()).await?;
+        let opening_tally = MapView::load(context.clone()).await?;
+        let next_match_id = RegisterView::load(context.clone()).await?;
+        let matches = MapView::load(context.clone()).await?;
+        let profiles = MapView::load(context.clone()).await?;
+        let handles = MapView::load(context.clone()).await?;
+        let session_keys = MapView::load(context.clone()).await?;
+        let relations = MapView::load(context).await?;
         
         Ok(Self {
             next_game_id,
             games,
             player_stats,
+            opening_tally,
+            next_match_id,
+            matches,
+            profiles,
+            handles,
+            session_keys,
+            relations,
         })
     }
 
@@ -210,6 +940,13 @@ impl View<Context> for ApplicationState {
         self.next_game_id.rollback().await?;
         self.games.rollback().await?;
         self.player_stats.rollback().await?;
+        self.opening_tally.rollback().await?;
+        self.next_match_id.rollback().await?;
+        self.matches.rollback().await?;
+        self.profiles.rollback().await?;
+        self.handles.rollback().await?;
+        self.session_keys.rollback().await?;
+        self.relations.rollback().await?;
         Ok(())
     }
 
@@ -217,6 +954,13 @@ impl View<Context> for ApplicationState {
         self.next_game_id.flush().await?;
         self.games.flush().await?;
         self.player_stats.flush().await?;
+        self.opening_tally.flush().await?;
+        self.next_match_id.flush().await?;
+        self.matches.flush().await?;
+        self.profiles.flush().await?;
+        self.handles.flush().await?;
+        self.session_keys.flush().await?;
+        self.relations.flush().await?;
         Ok(())
     }
 
@@ -224,6 +968,198 @@ impl View<Context> for ApplicationState {
         self.next_game_id.delete()?;
         self.games.delete()?;
         self.player_stats.delete()?;
+        self.opening_tally.delete()?;
+        self.next_match_id.delete()?;
+        self.matches.delete()?;
+        self.profiles.delete()?;
+        self.handles.delete()?;
+        self.session_keys.delete()?;
+        self.relations.delete()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn empty_state() -> ApplicationState {
+        ApplicationState::load(Context::default()).await.unwrap()
+    }
+
+    async fn register(
+        state: &mut ApplicationState,
+        player_id: &str,
+        handle: &str,
+    ) -> Result<Profile, TicTacToeError> {
+        state
+            .register_profile(
+                player_id.to_string(),
+                handle.to_string(),
+                player_id.to_uppercase(),
+                None,
+                Timestamp::from(0),
+            )
+            .await
+    }
+
+    async fn handle_owner(state: &ApplicationState, handle: &str) -> Option<PlayerId> {
+        let profile = state.get_profile_by_handle(handle).await.unwrap();
+        profile.map(|profile| profile.player_id)
+    }
+
+    #[tokio::test]
+    async fn test_handles_are_unique_in_any_case() {
+        let mut state = empty_state().await;
+        register(&mut state, "alice", "Wonder_1").await.unwrap();
+
+        assert!(matches!(
+            register(&mut state, "bob", "wonder_1").await,
+            Err(TicTacToeError::HandleTaken(_))
+        ));
+        assert_eq!(handle_owner(&state, "WONDER_1").await.as_deref(), Some("alice"));
+        assert!(state.get_profile(&"bob".to_string()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_renaming_releases_the_old_handle() {
+        let mut state = empty_state().await;
+        register(&mut state, "alice", "wonder").await.unwrap();
+
+        state.rename_profile("alice".to_string(), "looking_glass".to_string()).await.unwrap();
+        register(&mut state, "bob", "Wonder").await.unwrap();
+
+        assert_eq!(handle_owner(&state, "wonder").await.as_deref(), Some("bob"));
+        assert_eq!(handle_owner(&state, "looking_glass").await.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_players_can_change_the_case_of_their_own_handle() {
+        let mut state = empty_state().await;
+        register(&mut state, "alice", "wonder").await.unwrap();
+
+        let profile =
+            state.rename_profile("alice".to_string(), "WONDER".to_string()).await.unwrap();
+
+        assert_eq!(profile.handle, "WONDER");
+        assert_eq!(handle_owner(&state, "wonder").await.as_deref(), Some("alice"));
+        assert!(matches!(
+            register(&mut state, "bob", "Wonder").await,
+            Err(TicTacToeError::HandleTaken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_players_have_one_profile() {
+        let mut state = empty_state().await;
+        register(&mut state, "alice", "wonder").await.unwrap();
+
+        assert!(matches!(
+            register(&mut state, "alice", "looking_glass").await,
+            Err(TicTacToeError::ProfileExists)
+        ));
+        assert_eq!(handle_owner(&state, "looking_glass").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_games_show_profile_names() {
+        let mut state = empty_state().await;
+        register(&mut state, "alice", "wonder").await.unwrap();
+        let game = Game::new(1, "alice".to_string(), "alice".to_string(), Timestamp::from(0));
+
+        let game = state.with_profile_names(game).await.unwrap();
+        assert_eq!(game.players[0].name, "ALICE");
+    }
+
+    #[tokio::test]
+    async fn test_join_codes_survive_games_created_in_between() {
+        let mut state = empty_state().await;
+        let visibility = Visibility::Private {
+            invited: Vec::new(),
+            join_code_salt: 9_001,
+            join_code_hash: Some(crate::visibility::hash_join_code(9_001, "tuesday")),
+        };
+
+        // Someone else's game takes the id that was next when the code was hashed
+        let (settings, now) = (GameSettings::default(), Timestamp::from(0));
+        state
+            .create_game("bob".to_string(), "Bob".to_string(), settings, Visibility::Public, now)
+            .await
+            .unwrap();
+        let game_id = state
+            .create_game("alice".to_string(), "Alice".to_string(), settings, visibility, now)
+            .await
+            .unwrap();
+
+        let carol = |code: &str| ("carol".to_string(), "Carol".to_string(), Some(code.to_string()));
+        let (player_id, player_name, code) = carol("monday");
+        let result = state.join_game(game_id, player_id, player_name, code, now).await;
+        assert!(matches!(result, Err(TicTacToeError::NotInvited)));
+
+        let (player_id, player_name, code) = carol("tuesday");
+        state.join_game(game_id, player_id, player_name, code, now).await.unwrap();
+        let game = state.get_game(game_id).await.unwrap().unwrap();
+        assert_eq!(game.players[1].id, "carol");
+    }
+
+    #[tokio::test]
+    async fn test_blocked_players_cannot_join_a_match() {
+        let mut state = empty_state().await;
+        let series = state
+            .create_match(
+                "alice".to_string(),
+                "Alice".to_string(),
+                3,
+                GameSettings::default(),
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        state.block("alice".to_string(), "bob".to_string()).await.unwrap();
+
+        let result = state
+            .join_match(series.id, "bob".to_string(), "Bob".to_string(), Timestamp::from(1))
+            .await;
+        assert!(matches!(result, Err(TicTacToeError::BlockedByPlayer)));
+
+        let (series, game) = state
+            .join_match(series.id, "carol".to_string(), "Carol".to_string(), Timestamp::from(2))
+            .await
+            .unwrap();
+        assert_eq!(series.players[1].id, "carol");
+        assert_eq!(game.match_id, Some(series.id));
+    }
+
+    #[tokio::test]
+    async fn test_session_keys_cannot_be_squatted() {
+        let mut state = empty_state().await;
+        let browser = "browser".to_string();
+        let resign = |player_id: &str| Operation::Resign {
+            game_id: 1,
+            player_id: player_id.to_string(),
+        };
+        for player_id in ["mallory", "alice"] {
+            state
+                .authorize_session_key(
+                    player_id.to_string(),
+                    browser.clone(),
+                    Timestamp::from(100),
+                    SessionScope::AllGames,
+                    Timestamp::from(0),
+                )
+                .await
+                .unwrap();
+        }
+
+        let now = Timestamp::from(1);
+        assert!(state.authorize(&resign("alice"), Some(&browser), now).await.is_ok());
+        assert!(state.authorize(&resign("mallory"), Some(&browser), now).await.is_ok());
+
+        state.revoke_session_key(&"mallory".to_string(), &browser).await.unwrap();
+        assert!(state.authorize(&resign("alice"), Some(&browser), now).await.is_ok());
+        assert!(matches!(
+            state.authorize(&resign("mallory"), Some(&browser), now).await,
+            Err(TicTacToeError::SignerMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! Who can find and join a game.
+//!
+//! Public games are listed and open to anyone. Unlisted games are left out of
+//! listings but anyone with the game id may look them up and join. Private
+//! games are also left out of listings, are only shown to their players and
+//! invitees, and only admit invited players or whoever presents the join code.
+//! Only the hash of a join code is stored on chain, salted with a nonce the
+//! creator picks; the code itself is revealed by the join that uses it, when
+//! the seat is taken anyway.
+//!
+//! Queries take the viewer as a plain argument, and all chain state is public
+//! anyway, so hiding games only keeps them out of other players' views. It is
+//! not access control.
+
+use crate::PlayerId;
+use linera_base::crypto::{BcsHashable, CryptoHash};
+use serde::{Deserialize, Serialize};
+
+/// A join code and its salt, wrapped so that its hash cannot collide with
+/// other hashed values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JoinCode {
+    salt: u64,
+    code: String,
+}
+
+impl BcsHashable<'_> for JoinCode {}
+
+/// Hash of a join code salted with `salt`, as given to `Visibility::Private`
+/// together with the salt when creating the game. Picking a fresh random salt
+/// for every game makes the same code hash differently each time, so hashes
+/// cannot be looked up in a table built in advance. The salt is stored with
+/// the game, so the code opens it whatever id the game ends up with.
+pub fn hash_join_code(salt: u64, code: &str) -> CryptoHash {
+    CryptoHash::new(&JoinCode {
+        salt,
+        code: code.to_string(),
+    })
+}
+
+/// Who may see and join a game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Listed, and anyone may join
+    #[default]
+    Public,
+    /// Not listed, but anyone with the game id may join
+    Unlisted,
+    /// Not listed; only invited players or holders of the join code may join
+    Private {
+        invited: Vec<PlayerId>,
+        /// Nonce the join code is salted with
+        join_code_salt: u64,
+        /// Hash of the join code, see `hash_join_code`
+        join_code_hash: Option<CryptoHash>,
+    },
+}
+
+impl Visibility {
+    /// Whether the game shows up in game listings
+    pub fn is_listed(&self) -> bool {
+        matches!(self, Visibility::Public)
+    }
+
+    /// Whether `player_id` is on the invite list
+    pub fn is_invited(&self, player_id: &PlayerId) -> bool {
+        match self {
+            Visibility::Private { invited, .. } => invited.contains(player_id),
+            _ => false,
+        }
+    }
+
+    /// Whether anyone may look the game up by its id
+    pub fn is_open_by_id(&self) -> bool {
+        !matches!(self, Visibility::Private { .. })
+    }
+
+    /// Whether `player_id` may take a seat, given the join code they presented
+    pub fn admits(&self, player_id: &PlayerId, join_code: Option<&str>) -> bool {
+        match self {
+            Visibility::Public | Visibility::Unlisted => true,
+            Visibility::Private {
+                join_code_salt,
+                join_code_hash,
+                ..
+            } => {
+                self.is_invited(player_id)
+                    || join_code.zip(*join_code_hash).is_some_and(|(code, hash)| {
+                        hash_join_code(*join_code_salt, code) == hash
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_games_admit_invited_players_and_code_holders() {
+        let visibility = Visibility::Private {
+            invited: vec!["bob".to_string()],
+            join_code_salt: 7,
+            join_code_hash: Some(hash_join_code(7, "open sesame")),
+        };
+
+        assert!(!visibility.is_listed());
+        assert!(!visibility.is_open_by_id());
+        assert!(visibility.admits(&"bob".to_string(), None));
+        assert!(visibility.admits(&"carol".to_string(), Some("open sesame")));
+        assert!(!visibility.admits(&"carol".to_string(), Some("open sesame!")));
+        assert!(!visibility.admits(&"carol".to_string(), None));
+    }
+
+    #[test]
+    fn test_join_codes_are_checked_against_their_own_salt() {
+        assert_ne!(hash_join_code(1, "open sesame"), hash_join_code(2, "open sesame"));
+
+        let visibility = Visibility::Private {
+            invited: Vec::new(),
+            join_code_salt: 2,
+            join_code_hash: Some(hash_join_code(1, "open sesame")),
+        };
+        assert!(!visibility.admits(&"carol".to_string(), Some("open sesame")));
+    }
+
+    #[test]
+    fn test_private_game_without_a_code_needs_an_invite() {
+        let visibility = Visibility::Private {
+            invited: Vec::new(),
+            join_code_salt: 0,
+            join_code_hash: None,
+        };
+
+        assert!(!visibility.admits(&"bob".to_string(), Some("")));
+    }
+
+    #[test]
+    fn test_unlisted_games_are_open_but_hidden() {
+        assert!(Visibility::Public.is_listed());
+        assert!(!Visibility::Unlisted.is_listed());
+        assert!(Visibility::Unlisted.is_open_by_id());
+        assert!(Visibility::Unlisted.admits(&"anyone".to_string(), None));
+    }
+}
@@ -20,6 +20,18 @@ fn create_test_game(id: GameId, player_id: &str, player_name: &str) -> Game {
     )
 }
 
+/// Test helper to seat bob as the second player, starting any clock at 0s
+fn seat_bob(mut game: Game) -> Game {
+    game.join("bob".to_string(), "Bob".to_string(), mock_timestamp(0))
+        .unwrap();
+    game
+}
+
+/// Test helper to create a game alice and bob are both seated in
+fn create_started_game() -> Game {
+    seat_bob(create_test_game(1, "alice", "Alice"))
+}
+
 #[cfg(test)]
 mod game_logic_tests {
     use super::*;
@@ -264,6 +276,39 @@ mod player_stats_tests {
         assert_eq!(stats.losses, 1);
         assert_eq!(stats.draws, 1);
     }
+
+    #[test]
+    fn test_bot_games_do_not_count_as_ranked() {
+        let mut stats = PlayerStats::default();
+
+        stats.update_after_game(GameResult::Win);
+        stats.update_after_bot_game(GameResult::Win);
+        stats.update_after_bot_game(GameResult::Draw);
+
+        assert_eq!(stats.games_played, 1);
+        assert_eq!(stats.wins, 1);
+        assert_eq!(stats.bot_games_played, 2);
+        assert_eq!(stats.bot_wins, 1);
+        assert_eq!(stats.bot_draws, 1);
+        assert_eq!(stats.bot_losses, 0);
+    }
+
+    #[test]
+    fn test_series_are_counted_apart_from_games() {
+        let mut stats = PlayerStats::default();
+
+        stats.update_after_game(GameResult::Win);
+        stats.update_after_game(GameResult::Win);
+        stats.update_after_match(GameResult::Win);
+        stats.update_after_match(GameResult::Loss);
+        stats.update_after_match(GameResult::Draw);
+
+        assert_eq!(stats.games_played, 2);
+        assert_eq!(stats.matches_played, 3);
+        assert_eq!(stats.match_wins, 1);
+        assert_eq!(stats.match_losses, 1);
+        assert_eq!(stats.match_draws, 1);
+    }
 }
 
 #[cfg(test)]
@@ -378,3 +423,1011 @@ mod benchmark_tests {
         assert!(duration.as_micros() < 1000);
     }
 }
+
+#[cfg(test)]
+mod board_size_tests {
+    use super::*;
+    use linera_tic_tac_toe::BoardConfig;
+
+    fn create_sized_game(width: usize, height: usize, win_length: usize) -> Game {
+        let config = BoardConfig::new(width, height, win_length).unwrap();
+        seat_bob(Game::with_config(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            config,
+            mock_timestamp(0),
+        ))
+    }
+
+    #[test]
+    fn test_invalid_board_configs() {
+        assert!(matches!(
+            BoardConfig::new(0, 3, 3),
+            Err(TicTacToeError::InvalidBoardConfig { .. })
+        ));
+        assert!(matches!(
+            BoardConfig::new(3, 3, 4),
+            Err(TicTacToeError::InvalidBoardConfig { .. })
+        ));
+        assert!(matches!(
+            BoardConfig::new(20, 20, 5),
+            Err(TicTacToeError::InvalidBoardConfig { .. })
+        ));
+        assert!(BoardConfig::new(7, 6, 4).is_ok());
+    }
+
+    #[test]
+    fn test_classic_board_has_eight_lines() {
+        assert_eq!(BoardConfig::default().winning_lines().len(), 8);
+    }
+
+    #[test]
+    fn test_gomoku_board_creation() {
+        let game = create_sized_game(15, 15, 5);
+
+        assert_eq!(game.board.len(), 225);
+        assert_eq!(game.config.win_length, 5);
+    }
+
+    #[test]
+    fn test_invalid_position_uses_board_bounds() {
+        let mut game = create_sized_game(4, 4, 3);
+
+        game.make_move(&"alice".to_string(), 15, mock_timestamp(1)).unwrap();
+        let result = game.make_move(&"bob".to_string(), 16, mock_timestamp(2));
+        assert!(matches!(result, Err(TicTacToeError::InvalidPosition(16))));
+    }
+
+    #[test]
+    fn test_three_in_a_row_is_not_enough_for_gomoku() {
+        let mut game = create_sized_game(15, 15, 5);
+
+        // X plays 0, 1, 2 on the top row while O plays on the second row
+        for (i, (x, o)) in [(0, 15), (1, 16), (2, 17)].iter().enumerate() {
+            game.make_move(&"alice".to_string(), *x, mock_timestamp(2 * i as u64 + 1)).unwrap();
+            game.make_move(&"bob".to_string(), *o, mock_timestamp(2 * i as u64 + 2)).unwrap();
+        }
+
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert_eq!(game.winner, None);
+    }
+
+    #[test]
+    fn test_gomoku_diagonal_win() {
+        let mut game = create_sized_game(15, 15, 5);
+
+        // X builds an anti-diagonal from (row 2, col 10) down to (row 6, col 6)
+        let x_moves = [40, 54, 68, 82, 96];
+        let o_moves = [0, 1, 2, 3];
+        for i in 0..4 {
+            let turn = 2 * i as u64;
+            game.make_move(&"alice".to_string(), x_moves[i], mock_timestamp(turn + 1)).unwrap();
+            game.make_move(&"bob".to_string(), o_moves[i], mock_timestamp(turn + 2)).unwrap();
+        }
+        game.make_move(&"alice".to_string(), x_moves[4], mock_timestamp(9)).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::X));
+    }
+
+    #[test]
+    fn test_line_does_not_wrap_around_rows() {
+        let mut game = create_sized_game(4, 4, 3);
+
+        // Cells 2, 3 and 4 are consecutive indices but span two rows
+        game.make_move(&"alice".to_string(), 2, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 8, mock_timestamp(2)).unwrap();
+        game.make_move(&"alice".to_string(), 3, mock_timestamp(3)).unwrap();
+        game.make_move(&"bob".to_string(), 9, mock_timestamp(4)).unwrap();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(5)).unwrap();
+
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert_eq!(game.winner, None);
+    }
+}
+
+#[cfg(test)]
+mod rule_options_tests {
+    use super::*;
+    use linera_tic_tac_toe::{Move, RuleOptions};
+
+    fn create_game_with_options(options: RuleOptions) -> Game {
+        seat_bob(create_test_game(1, "alice", "Alice").with_options(options))
+    }
+
+    #[test]
+    fn test_misere_line_loses() {
+        let mut game = create_game_with_options(RuleOptions {
+            misere: true,
+            wild: false,
+            early_draw: false,
+        });
+
+        // Alice (X) completes the top row and loses
+        game.make_move(&"alice".to_string(), 0, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 3, mock_timestamp(2)).unwrap();
+        game.make_move(&"alice".to_string(), 1, mock_timestamp(3)).unwrap();
+        game.make_move(&"bob".to_string(), 4, mock_timestamp(4)).unwrap();
+        game.make_move(&"alice".to_string(), 2, mock_timestamp(5)).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::O));
+        assert_eq!(game.winner_id, Some("bob".to_string()));
+    }
+
+    #[test]
+    fn test_wild_player_wins_with_opponent_symbol() {
+        let mut game = create_game_with_options(RuleOptions {
+            misere: false,
+            wild: true,
+            early_draw: false,
+        });
+
+        // Alice completes a column of O's on her own turn
+        let o = |position| Move::at(position).with_symbol(Player::O);
+        game.play(&"alice".to_string(), o(0), mock_timestamp(1)).unwrap();
+        game.play(&"bob".to_string(), Move::at(1), mock_timestamp(2)).unwrap();
+        game.play(&"alice".to_string(), o(3), mock_timestamp(3)).unwrap();
+        game.play(&"bob".to_string(), Move::at(8), mock_timestamp(4)).unwrap();
+        game.play(&"alice".to_string(), o(6), mock_timestamp(5)).unwrap();
+
+        assert_eq!(game.board[6], Some(Player::O));
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::X));
+        assert_eq!(game.winner_id, Some("alice".to_string()));
+    }
+
+    #[test]
+    fn test_wild_and_misere_combined() {
+        let mut game = create_game_with_options(RuleOptions {
+            misere: true,
+            wild: true,
+            early_draw: false,
+        });
+
+        let x = |position| Move::at(position).with_symbol(Player::X);
+        game.play(&"alice".to_string(), x(0), mock_timestamp(1)).unwrap();
+        game.play(&"bob".to_string(), x(1), mock_timestamp(2)).unwrap();
+        // Alice completes the X row herself and loses
+        game.play(&"alice".to_string(), x(2), mock_timestamp(3)).unwrap();
+
+        assert_eq!(game.winner_id, Some("bob".to_string()));
+    }
+
+    #[test]
+    fn test_symbol_choice_requires_wild() {
+        let mut game = create_game_with_options(RuleOptions::default());
+
+        let result = game.play(
+            &"alice".to_string(),
+            Move::at(0).with_symbol(Player::O),
+            mock_timestamp(1),
+        );
+        assert!(matches!(result, Err(TicTacToeError::SymbolNotAllowed)));
+
+        // Naming your own symbol is always fine
+        let result = game.play(
+            &"alice".to_string(),
+            Move::at(0).with_symbol(Player::X),
+            mock_timestamp(1),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_standard_winner_identity() {
+        let mut game = create_game_with_options(RuleOptions::default());
+
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+        game.make_move(&"alice".to_string(), 3, mock_timestamp(3)).unwrap();
+        game.make_move(&"bob".to_string(), 1, mock_timestamp(4)).unwrap();
+        game.make_move(&"alice".to_string(), 5, mock_timestamp(5)).unwrap();
+
+        assert_eq!(game.winner, Some(Player::X));
+        assert_eq!(game.winner_id, Some("alice".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod move_history_tests {
+    use super::*;
+
+    #[test]
+    fn test_moves_are_recorded_in_order() {
+        let mut game = create_started_game();
+
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+
+        assert_eq!(game.moves.len(), 2);
+        assert_eq!(game.moves[0].player_id, "alice");
+        assert_eq!(game.moves[0].position, 4);
+        assert_eq!(game.moves[0].mv.symbol, Some(Player::X));
+        assert_eq!(game.moves[0].timestamp, mock_timestamp(1));
+        assert_eq!(game.moves[1].player_id, "bob");
+        assert_eq!(game.moves[1].position, 0);
+    }
+
+    #[test]
+    fn test_accepted_takeback_undoes_last_move() {
+        let mut game = create_started_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+
+        game.request_takeback(&"bob".to_string()).unwrap();
+        game.accept_takeback(&"alice".to_string()).unwrap();
+
+        assert_eq!(game.moves.len(), 1);
+        assert_eq!(game.board[0], None);
+        assert_eq!(game.board[4], Some(Player::X));
+        assert_eq!(game.current_player, Player::O);
+        assert_eq!(game.takeback_requested_by, None);
+    }
+
+    #[test]
+    fn test_takeback_needs_the_opponent() {
+        let mut game = create_started_game();
+
+        let result = game.request_takeback(&"alice".to_string());
+        assert!(matches!(result, Err(TicTacToeError::NoMoveToTakeBack)));
+
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        let result = game.accept_takeback(&"bob".to_string());
+        assert!(matches!(result, Err(TicTacToeError::NoTakebackRequested)));
+
+        game.request_takeback(&"alice".to_string()).unwrap();
+        let result = game.accept_takeback(&"alice".to_string());
+        assert!(matches!(result, Err(TicTacToeError::CannotAcceptOwnTakeback)));
+
+        let result = game.request_takeback(&"charlie".to_string());
+        assert!(matches!(result, Err(TicTacToeError::PlayerNotInGame)));
+    }
+
+    #[test]
+    fn test_only_the_last_mover_can_ask_for_a_takeback() {
+        let mut game = create_started_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+
+        let result = game.request_takeback(&"bob".to_string());
+        assert!(matches!(result, Err(TicTacToeError::NotYourMove)));
+        assert_eq!(game.takeback_requested_by, None);
+    }
+
+    #[test]
+    fn test_new_move_cancels_takeback_request() {
+        let mut game = create_started_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        game.request_takeback(&"alice".to_string()).unwrap();
+
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+
+        assert_eq!(game.takeback_requested_by, None);
+        let result = game.accept_takeback(&"bob".to_string());
+        assert!(matches!(result, Err(TicTacToeError::NoTakebackRequested)));
+    }
+
+    #[test]
+    fn test_takeback_restores_ultimate_meta_board() {
+        use linera_tic_tac_toe::{BoardConfig, GameVariant, Move};
+
+        let mut game = seat_bob(Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            GameVariant::Ultimate,
+            BoardConfig::default(),
+            mock_timestamp(0),
+        ));
+        game.play(&"alice".to_string(), Move::in_sub_board(4, 2), mock_timestamp(1)).unwrap();
+        game.play(&"bob".to_string(), Move::in_sub_board(2, 7), mock_timestamp(2)).unwrap();
+
+        game.request_takeback(&"bob".to_string()).unwrap();
+        game.accept_takeback(&"alice".to_string()).unwrap();
+
+        // Only alice's move is left on the board
+        assert_eq!(game.board.iter().flatten().count(), 1);
+        assert_eq!(game.ultimate.as_ref().unwrap().next_board, Some(2));
+        assert_eq!(game.current_player, Player::O);
+    }
+}
+
+#[cfg(test)]
+mod move_at_tests {
+    use super::*;
+    use linera_tic_tac_toe::{BoardConfig, GameVariant, Move};
+
+    fn variant_game(variant: GameVariant) -> Game {
+        seat_bob(Game::with_variant(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            variant,
+            BoardConfig::default(),
+            mock_timestamp(0),
+        ))
+    }
+
+    /// Play the cell at (x, y, z) as alice, the way `MakeMoveAt` does
+    fn play_at(game: &mut Game, x: usize, y: usize, z: usize) -> Result<(), TicTacToeError> {
+        let mv = game.move_at(x, y, z)?;
+        game.play(&"alice".to_string(), mv, mock_timestamp(1))
+    }
+
+    #[test]
+    fn test_standard_coordinates_are_row_major() {
+        let mut game = variant_game(GameVariant::Standard);
+
+        play_at(&mut game, 2, 1, 0).unwrap();
+        assert_eq!(game.board[5], Some(Player::X));
+        assert!(matches!(
+            game.move_at(3, 0, 0),
+            Err(TicTacToeError::InvalidCoordinates { x: 3, y: 0, z: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_qubic_coordinates_reach_every_layer() {
+        let mut game = variant_game(GameVariant::Qubic);
+
+        play_at(&mut game, 1, 2, 3).unwrap();
+        assert_eq!(game.board[(3 * 4 + 2) * 4 + 1], Some(Player::X));
+    }
+
+    #[test]
+    fn test_gravity_coordinates_drop_into_the_column() {
+        let mut game = variant_game(GameVariant::Gravity);
+
+        // Clicking the top of column 3 lands on the bottom row
+        play_at(&mut game, 3, 0, 0).unwrap();
+        assert_eq!(game.board[5 * 7 + 3], Some(Player::X));
+        assert_eq!(game.move_at(3, 4, 0).unwrap(), Move::at(3));
+        assert!(game.move_at(3, 6, 0).is_err());
+    }
+
+    #[test]
+    fn test_ultimate_coordinates_pick_board_and_cell() {
+        let mut game = variant_game(GameVariant::Ultimate);
+
+        // Column 7, row 1 is the centre of the top-right local board
+        assert_eq!(game.move_at(7, 1, 0).unwrap(), Move::in_sub_board(2, 4));
+        play_at(&mut game, 7, 1, 0).unwrap();
+        assert_eq!(game.board[game.config.position(7, 1, 0).unwrap()], Some(Player::X));
+        assert_eq!(game.ultimate.as_ref().unwrap().next_board, Some(4));
+        assert!(game.move_at(9, 0, 0).is_err());
+    }
+}
+
+#[cfg(test)]
+mod outcome_tests {
+    use super::*;
+    use linera_tic_tac_toe::FinishReason;
+
+    #[test]
+    fn test_line_win_records_winning_cells() {
+        let mut game = create_started_game();
+        assert_eq!(game.outcome, None);
+
+        game.make_move(&"alice".to_string(), 2, mock_timestamp(1)).unwrap();
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(3)).unwrap();
+        game.make_move(&"bob".to_string(), 1, mock_timestamp(4)).unwrap();
+        game.make_move(&"alice".to_string(), 6, mock_timestamp(5)).unwrap();
+
+        let outcome = game.outcome.expect("finished games have an outcome");
+        assert_eq!(outcome.reason, FinishReason::LineCompleted);
+        assert_eq!(outcome.winning_line, vec![2, 4, 6]);
+        assert_eq!(outcome.move_number, 5);
+    }
+
+    #[test]
+    fn test_full_board_draw_has_no_line() {
+        let mut game = create_started_game();
+
+        // X X O / O O X / X O X
+        let moves = [0, 2, 1, 3, 5, 4, 6, 7, 8];
+        for (i, position) in moves.iter().enumerate() {
+            let player = if i % 2 == 0 { "alice" } else { "bob" };
+            game.make_move(&player.to_string(), *position, mock_timestamp(i as u64 + 1)).unwrap();
+        }
+
+        assert_eq!(game.winner, None);
+        let outcome = game.outcome.expect("finished games have an outcome");
+        assert_eq!(outcome.reason, FinishReason::BoardFull);
+        assert!(outcome.winning_line.is_empty());
+        assert_eq!(outcome.move_number, 9);
+    }
+}
+
+#[cfg(test)]
+mod early_draw_tests {
+    use super::*;
+    use linera_tic_tac_toe::{FinishReason, RuleOptions};
+
+    // X X O / O O X / X _ O: every line holds both symbols after eight moves
+    const DEAD_POSITION: [usize; 8] = [0, 4, 1, 2, 6, 3, 5, 8];
+
+    /// Play `moves` alternately, the first of them being move number `start`
+    fn play_moves(game: &mut Game, start: usize, moves: &[usize]) {
+        for (i, position) in moves.iter().enumerate() {
+            let turn = start + i;
+            let player = if turn % 2 == 0 { "alice" } else { "bob" };
+            game.make_move(&player.to_string(), *position, mock_timestamp(turn as u64 + 1)).unwrap();
+        }
+    }
+
+    fn create_game_with_early_draw(early_draw: bool) -> Game {
+        let options = RuleOptions {
+            early_draw,
+            ..RuleOptions::default()
+        };
+        seat_bob(create_test_game(1, "alice", "Alice").with_options(options))
+    }
+
+    #[test]
+    fn test_dead_position_ends_in_draw() {
+        let mut game = create_game_with_early_draw(true);
+
+        play_moves(&mut game, 0, &DEAD_POSITION[..7]);
+        assert_eq!(game.status, GameStatus::InProgress);
+
+        play_moves(&mut game, 7, &DEAD_POSITION[7..]);
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.finished_at, Some(mock_timestamp(8)));
+        assert_eq!(game.board[7], None);
+        assert_eq!(game.outcome.unwrap().reason, FinishReason::NoLineLeft);
+    }
+
+    #[test]
+    fn test_dead_position_plays_on_without_option() {
+        let mut game = create_game_with_early_draw(false);
+
+        play_moves(&mut game, 0, &DEAD_POSITION);
+        assert_eq!(game.status, GameStatus::InProgress);
+    }
+
+    #[test]
+    fn test_early_draw_on_larger_board() {
+        use linera_tic_tac_toe::BoardConfig;
+
+        let options = RuleOptions {
+            early_draw: true,
+            ..RuleOptions::default()
+        };
+        let config = BoardConfig::new(4, 1, 3).unwrap();
+        let mut game = seat_bob(
+            Game::with_config(
+                1,
+                "alice".to_string(),
+                "Alice".to_string(),
+                config,
+                mock_timestamp(0),
+            )
+            .with_options(options),
+        );
+
+        // On a 4x1 strip with three in a row, O in cell 1 and X in cell 2 block both lines
+        play_moves(&mut game, 0, &[2, 1]);
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.outcome.unwrap().reason, FinishReason::NoLineLeft);
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+    use linera_tic_tac_toe::{FinishReason, TimeControl};
+
+    /// A game with a minute per player and a five second increment, started at 0s
+    fn timed_game() -> Game {
+        let control = TimeControl::new(60, 5).unwrap();
+        seat_bob(create_test_game(1, "alice", "Alice").with_time_control(control))
+    }
+
+    #[test]
+    fn test_clock_starts_when_the_opponent_joins() {
+        let game = timed_game();
+        let clock = game.clock.as_ref().unwrap();
+
+        assert_eq!(clock.turn_started_at, Some(mock_timestamp(0)));
+        assert_eq!(clock.remaining(Player::X, Player::X, mock_timestamp(20)), 40_000_000);
+    }
+
+    #[test]
+    fn test_timeout_can_be_claimed_by_the_waiting_player() {
+        let mut game = timed_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(10)).unwrap();
+
+        // Bob has 60s from 10s; at 69s his clock has not run out
+        let early = game.claim_timeout(&"alice".to_string(), mock_timestamp(69));
+        assert!(matches!(early, Err(TicTacToeError::OpponentHasTime)));
+
+        game.claim_timeout(&"alice".to_string(), mock_timestamp(70)).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner_id.as_deref(), Some("alice"));
+        assert_eq!(game.outcome.unwrap().reason, FinishReason::Timeout);
+        assert_eq!(game.finished_at, Some(mock_timestamp(70)));
+    }
+
+    #[test]
+    fn test_flagged_player_can_neither_move_nor_claim() {
+        let mut game = timed_game();
+
+        let late_move = game.make_move(&"alice".to_string(), 4, mock_timestamp(61));
+        assert!(matches!(late_move, Err(TicTacToeError::OutOfTime)));
+
+        let own_claim = game.claim_timeout(&"alice".to_string(), mock_timestamp(61));
+        assert!(matches!(own_claim, Err(TicTacToeError::OpponentHasTime)));
+        assert_eq!(game.status, GameStatus::InProgress);
+    }
+
+    #[test]
+    fn test_untimed_games_cannot_time_out() {
+        let mut game = create_started_game();
+
+        let result = game.claim_timeout(&"bob".to_string(), mock_timestamp(1_000_000));
+        assert!(matches!(result, Err(TicTacToeError::GameNotTimed)));
+    }
+
+    #[test]
+    fn test_timed_games_have_no_takebacks() {
+        let mut game = timed_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(10)).unwrap();
+
+        let result = game.request_takeback(&"alice".to_string());
+        assert!(matches!(result, Err(TicTacToeError::TakebackInTimedGame)));
+        assert_eq!(game.takeback_requested_by, None);
+        assert_eq!(game.clock.as_ref().unwrap().x_remaining_micros, 55_000_000);
+    }
+}
+
+#[cfg(test)]
+mod resignation_tests {
+    use super::*;
+    use linera_tic_tac_toe::FinishReason;
+
+    #[test]
+    fn test_resignation_hands_the_win_to_the_opponent() {
+        let mut game = create_started_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+
+        // Resigning does not have to wait for one's turn
+        game.resign(&"alice".to_string(), mock_timestamp(2)).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(Player::O));
+        assert_eq!(game.winner_id.as_deref(), Some("bob"));
+        assert_eq!(game.finished_at, Some(mock_timestamp(2)));
+        let outcome = game.outcome.unwrap();
+        assert_eq!(outcome.reason, FinishReason::Resignation);
+        assert!(outcome.winning_line.is_empty());
+    }
+
+    #[test]
+    fn test_only_games_in_progress_can_be_resigned() {
+        let mut game = create_test_game(1, "alice", "Alice");
+        let waiting = game.resign(&"alice".to_string(), mock_timestamp(1));
+        assert!(matches!(waiting, Err(TicTacToeError::GameNotInProgress)));
+
+        game.add_player("bob".to_string(), "Bob".to_string()).unwrap();
+        let outsider = game.resign(&"charlie".to_string(), mock_timestamp(1));
+        assert!(matches!(outsider, Err(TicTacToeError::PlayerNotInGame)));
+
+        game.resign(&"bob".to_string(), mock_timestamp(2)).unwrap();
+        let again = game.resign(&"alice".to_string(), mock_timestamp(3));
+        assert!(matches!(again, Err(TicTacToeError::GameNotInProgress)));
+        assert_eq!(game.winner_id.as_deref(), Some("alice"));
+    }
+}
+
+#[cfg(test)]
+mod draw_offer_tests {
+    use super::*;
+    use linera_tic_tac_toe::FinishReason;
+
+    #[test]
+    fn test_accepted_offer_ends_in_a_draw() {
+        let mut game = create_started_game();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        game.offer_draw(&"alice".to_string()).unwrap();
+        assert_eq!(game.draw_offered_by.as_deref(), Some("alice"));
+
+        game.respond_to_draw(&"bob".to_string(), true, mock_timestamp(2)).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.winner_id, None);
+        assert_eq!(game.draw_offered_by, None);
+        assert_eq!(game.outcome.unwrap().reason, FinishReason::AgreedDraw);
+        assert_eq!(game.finished_at, Some(mock_timestamp(2)));
+    }
+
+    #[test]
+    fn test_declined_offer_plays_on() {
+        let mut game = create_started_game();
+        game.offer_draw(&"bob".to_string()).unwrap();
+
+        let own = game.respond_to_draw(&"bob".to_string(), true, mock_timestamp(1));
+        assert!(matches!(own, Err(TicTacToeError::CannotAnswerOwnDrawOffer)));
+
+        game.respond_to_draw(&"alice".to_string(), false, mock_timestamp(1)).unwrap();
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert_eq!(game.draw_offered_by, None);
+
+        let nothing = game.respond_to_draw(&"alice".to_string(), true, mock_timestamp(2));
+        assert!(matches!(nothing, Err(TicTacToeError::NoDrawOffered)));
+    }
+
+    #[test]
+    fn test_offer_lapses_when_the_opponent_moves() {
+        let mut game = create_started_game();
+
+        // Offering with one's own move keeps the offer open
+        game.offer_draw(&"alice".to_string()).unwrap();
+        game.make_move(&"alice".to_string(), 4, mock_timestamp(1)).unwrap();
+        assert_eq!(game.draw_offered_by.as_deref(), Some("alice"));
+
+        game.make_move(&"bob".to_string(), 0, mock_timestamp(2)).unwrap();
+        assert_eq!(game.draw_offered_by, None);
+        let late = game.respond_to_draw(&"bob".to_string(), true, mock_timestamp(3));
+        assert!(matches!(late, Err(TicTacToeError::NoDrawOffered)));
+    }
+}
+
+#[cfg(test)]
+mod rematch_tests {
+    use super::*;
+    use linera_tic_tac_toe::{Difficulty, TimeControl};
+
+    /// A game alice won as X
+    fn finished_game() -> Game {
+        let control = TimeControl::new(60, 0).unwrap();
+        let mut game = seat_bob(create_test_game(1, "alice", "Alice").with_time_control(control));
+        for (turn, position) in [0, 3, 1, 4, 2].into_iter().enumerate() {
+            let player = if turn.is_multiple_of(2) { "alice" } else { "bob" };
+            game.make_move(&player.to_string(), position, mock_timestamp(turn as u64 + 1))
+                .unwrap();
+        }
+        assert_eq!(game.winner_id.as_deref(), Some("alice"));
+        game
+    }
+
+    #[test]
+    fn test_rematch_swaps_symbols_and_links_games() {
+        let mut game = finished_game();
+        game.offer_rematch(&"alice".to_string()).unwrap();
+        let rematch = game
+            .accept_rematch(&"bob".to_string(), 2, mock_timestamp(10))
+            .unwrap();
+
+        assert_eq!(rematch.id, 2);
+        assert_eq!(rematch.previous_game_id, Some(1));
+        assert_eq!(game.rematch_game_id, Some(2));
+        assert_eq!(game.rematch_offered_by, None);
+        assert_eq!(rematch.status, GameStatus::InProgress);
+        assert_eq!(rematch.players[0].id, "bob");
+        assert_eq!(rematch.players[0].symbol, Player::X);
+        assert_eq!(rematch.players[1].id, "alice");
+        assert_eq!(rematch.players[1].symbol, Player::O);
+        assert_eq!(rematch.player_to_move().unwrap().id, "bob");
+
+        // The time control carries over with fresh clocks
+        let clock = rematch.clock.as_ref().unwrap();
+        assert_eq!(clock.x_remaining_micros, 60_000_000);
+        assert_eq!(clock.turn_started_at, Some(mock_timestamp(10)));
+    }
+
+    #[test]
+    fn test_rematch_needs_a_finished_game_and_the_opponent() {
+        let mut game = create_started_game();
+        let early = game.offer_rematch(&"alice".to_string());
+        assert!(matches!(early, Err(TicTacToeError::GameNotFinished)));
+
+        let mut game = finished_game();
+        let unoffered = game.accept_rematch(&"bob".to_string(), 2, mock_timestamp(10));
+        assert!(matches!(unoffered, Err(TicTacToeError::NoRematchOffered)));
+
+        game.offer_rematch(&"bob".to_string()).unwrap();
+        let own = game.accept_rematch(&"bob".to_string(), 2, mock_timestamp(10));
+        assert!(matches!(own, Err(TicTacToeError::CannotAcceptOwnRematch)));
+
+        game.accept_rematch(&"alice".to_string(), 2, mock_timestamp(10)).unwrap();
+        let again = game.offer_rematch(&"bob".to_string());
+        assert!(matches!(again, Err(TicTacToeError::RematchAlreadyCreated)));
+    }
+
+    #[test]
+    fn test_match_games_cannot_be_rematched() {
+        let mut game = finished_game();
+        game.match_id = Some(1);
+
+        let result = game.offer_rematch(&"alice".to_string());
+        assert!(matches!(result, Err(TicTacToeError::GameInMatch)));
+    }
+
+    #[test]
+    fn test_bot_rematch_keeps_the_bot() {
+        let mut game = Game::against_bot(
+            1,
+            "alice".to_string(),
+            "Alice".to_string(),
+            Difficulty::Hard,
+            mock_timestamp(0),
+        );
+        game.resign(&"alice".to_string(), mock_timestamp(1)).unwrap();
+        game.offer_rematch(&"alice".to_string()).unwrap();
+        let rematch = game
+            .accept_rematch(&"bot".to_string(), 2, mock_timestamp(2))
+            .unwrap();
+
+        assert_eq!(rematch.bot, Some(Difficulty::Hard));
+        assert_eq!(rematch.players[0].id, "bot");
+        assert_eq!(rematch.players[0].symbol, Player::X);
+    }
+}
+
+#[cfg(test)]
+mod authorization_tests {
+    use super::*;
+    use linera_tic_tac_toe::{SessionKey, SessionScope};
+
+    fn resign_as(player_id: &str) -> Operation {
+        Operation::Resign {
+            game_id: 1,
+            player_id: player_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_operation_signed_by_its_player_is_accepted() {
+        let operation = resign_as("alice");
+
+        assert_eq!(operation.player_id(), "alice");
+        assert!(operation.authorize(Some(&"alice".to_string())).is_ok());
+    }
+
+    #[test]
+    fn test_acting_for_someone_else_is_rejected() {
+        let result = resign_as("alice").authorize(Some(&"mallory".to_string()));
+
+        match result {
+            Err(TicTacToeError::SignerMismatch { claimed, signer }) => {
+                assert_eq!(claimed, "alice");
+                assert_eq!(signer, "mallory");
+            }
+            other => panic!("expected a signer mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsigned_operations_are_rejected() {
+        let operation = Operation::JoinGame {
+            game_id: 1,
+            player_id: "alice".to_string(),
+            player_name: "Alice".to_string(),
+            join_code: None,
+        };
+
+        assert!(matches!(operation.authorize(None), Err(TicTacToeError::Unauthenticated)));
+    }
+
+    #[test]
+    fn test_session_keys_reach_only_games_in_play() {
+        let session = SessionKey::new(
+            "browser".to_string(),
+            "alice".to_string(),
+            mock_timestamp(60),
+            SessionScope::AllGames,
+            mock_timestamp(0),
+        )
+        .unwrap();
+        let join = Operation::JoinGame {
+            game_id: 1,
+            player_id: "alice".to_string(),
+            player_name: "Alice".to_string(),
+            join_code: None,
+        };
+
+        assert_eq!(resign_as("alice").game_id(), Some(1));
+        assert!(session.permits(&resign_as("alice"), mock_timestamp(1)).is_ok());
+        assert_eq!(join.game_id(), None);
+        assert!(matches!(
+            session.permits(&join, mock_timestamp(1)),
+            Err(TicTacToeError::SessionKeyOutOfScope)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod visibility_tests {
+    use super::*;
+    use linera_tic_tac_toe::{visibility::hash_join_code, Visibility};
+
+    fn private_game() -> Game {
+        create_test_game(1, "alice", "Alice").with_visibility(Visibility::Private {
+            invited: vec!["bob".to_string()],
+            join_code_salt: 42,
+            join_code_hash: Some(hash_join_code(42, "tuesday")),
+        })
+    }
+
+    #[test]
+    fn test_players_cannot_join_their_own_game() {
+        let mut game = create_test_game(1, "alice", "Alice");
+
+        let result = game.add_player("alice".to_string(), "Alice again".to_string());
+        assert!(matches!(result, Err(TicTacToeError::AlreadyInGame)));
+        assert_eq!(game.players.len(), 1);
+        assert_eq!(game.status, GameStatus::WaitingForPlayer);
+    }
+
+    #[test]
+    fn test_private_game_admission() {
+        let game = private_game();
+
+        assert!(game.check_admission(&"bob".to_string(), None).is_ok());
+        assert!(game.check_admission(&"carol".to_string(), Some("tuesday")).is_ok());
+        assert!(matches!(
+            game.check_admission(&"carol".to_string(), Some("monday")),
+            Err(TicTacToeError::NotInvited)
+        ));
+        assert!(matches!(
+            game.check_admission(&"alice".to_string(), Some("tuesday")),
+            Err(TicTacToeError::AlreadyInGame)
+        ));
+    }
+
+    #[test]
+    fn test_hidden_games_are_listed_only_for_their_players() {
+        let game = seat_bob(private_game());
+        let unlisted = create_test_game(2, "carol", "Carol").with_visibility(Visibility::Unlisted);
+
+        assert!(!game.is_visible_to(None));
+        assert!(!game.is_visible_to(Some(&"carol".to_string())));
+        assert!(game.is_visible_to(Some(&"alice".to_string())));
+        assert!(game.is_visible_to(Some(&"bob".to_string())));
+        assert!(!unlisted.is_visible_to(None));
+        assert!(unlisted.is_visible_to(Some(&"carol".to_string())));
+        assert!(create_test_game(3, "dave", "Dave").is_visible_to(None));
+    }
+
+    #[test]
+    fn test_only_private_games_are_hidden_from_lookups_by_id() {
+        let game = private_game();
+        let unlisted = create_test_game(2, "carol", "Carol").with_visibility(Visibility::Unlisted);
+
+        assert!(!game.is_reachable_by(None));
+        assert!(!game.is_reachable_by(Some(&"carol".to_string())));
+        assert!(game.is_reachable_by(Some(&"alice".to_string())));
+        assert!(game.is_reachable_by(Some(&"bob".to_string())));
+        assert!(unlisted.is_reachable_by(None));
+    }
+}
+
+#[cfg(test)]
+mod challenge_tests {
+    use super::*;
+    use linera_tic_tac_toe::{ChallengeRules, FinishReason};
+
+    fn challenge_to_bob() -> Game {
+        ChallengeRules::default()
+            .new_game(
+                1,
+                "alice".to_string(),
+                "Alice".to_string(),
+                "bob".to_string(),
+                mock_timestamp(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_declined_challenge_ends_without_a_winner() {
+        let mut game = challenge_to_bob();
+
+        game.decline_challenge(&"bob".to_string(), mock_timestamp(5)).unwrap();
+
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner_id, None);
+        assert_eq!(game.outcome.unwrap().reason, FinishReason::Declined);
+        assert_eq!(game.finished_at, Some(mock_timestamp(5)));
+    }
+
+    #[test]
+    fn test_declined_challenge_cannot_be_rematched() {
+        let mut game = challenge_to_bob();
+        game.decline_challenge(&"bob".to_string(), mock_timestamp(5)).unwrap();
+
+        assert!(matches!(
+            game.offer_rematch(&"alice".to_string()),
+            Err(TicTacToeError::NothingToRematch)
+        ));
+        assert_eq!(game.rematch_offered_by, None);
+    }
+
+    #[test]
+    fn test_only_the_challenged_player_can_decline() {
+        let mut game = challenge_to_bob();
+
+        for player_id in ["alice", "carol"] {
+            assert!(matches!(
+                game.decline_challenge(&player_id.to_string(), mock_timestamp(5)),
+                Err(TicTacToeError::NotChallenged)
+            ));
+        }
+        assert_eq!(game.status, GameStatus::WaitingForPlayer);
+    }
+
+    #[test]
+    fn test_accepted_challenge_cannot_be_declined() {
+        let mut game = seat_bob(challenge_to_bob());
+
+        assert_eq!(game.status, GameStatus::InProgress);
+        assert!(matches!(
+            game.decline_challenge(&"bob".to_string(), mock_timestamp(6)),
+            Err(TicTacToeError::ChallengeClosed)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod match_tests {
+    use super::*;
+    use linera_tic_tac_toe::GameSettings;
+    use linera_views::{common::Context, views::View};
+
+    async fn state_with_match(best_of: u32) -> ApplicationState {
+        let mut state = ApplicationState::load(Context::default()).await.unwrap();
+        let settings = GameSettings::default();
+        let (player_id, player_name) = ("alice".to_string(), "Alice".to_string());
+        state
+            .create_match(player_id, player_name, best_of, settings, mock_timestamp(0))
+            .await
+            .unwrap();
+        state
+    }
+
+    #[tokio::test]
+    async fn test_open_matches_can_be_found_and_joined() {
+        let mut state = state_with_match(3).await;
+
+        let open = state.get_open_matches().await.unwrap();
+        assert_eq!(open.len(), 1);
+        let created = state.get_player_matches(&"alice".to_string()).await.unwrap();
+        assert_eq!(created, open);
+
+        let (series, game) = state
+            .join_match(open[0].id, "bob".to_string(), "Bob".to_string(), mock_timestamp(1))
+            .await
+            .unwrap();
+        assert_eq!(game.match_id, Some(series.id));
+        assert!(state.get_open_matches().await.unwrap().is_empty());
+        let joined = state.get_player_matches(&"bob".to_string()).await.unwrap();
+        assert_eq!(joined, vec![series]);
+    }
+
+    #[tokio::test]
+    async fn test_match_games_count_only_towards_the_match() {
+        let mut state = state_with_match(1).await;
+        let match_id = state.get_open_matches().await.unwrap()[0].id;
+        let (_, game) = state
+            .join_match(match_id, "bob".to_string(), "Bob".to_string(), mock_timestamp(1))
+            .await
+            .unwrap();
+
+        let (_, next) = state
+            .resign(game.id, "alice".to_string(), mock_timestamp(2))
+            .await
+            .unwrap();
+        assert!(next.is_none());
+
+        let alice = state.get_player_stats(&"alice".to_string()).await.unwrap();
+        let bob = state.get_player_stats(&"bob".to_string()).await.unwrap();
+        assert_eq!((alice.games_played, alice.losses), (0, 0));
+        assert_eq!((alice.matches_played, alice.match_losses), (1, 1));
+        assert_eq!((bob.games_played, bob.wins), (0, 0));
+        assert_eq!((bob.matches_played, bob.match_wins), (1, 1));
+    }
+}